@@ -0,0 +1,139 @@
+//! Thousands of bullets and invaders on screen at once, run with `cargo bench`
+
+use std::time::{Duration, Instant};
+
+use bevy::prelude::*;
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use space_invaders::{
+    cli::CliArgs, collision::SpatialGrid, game::GamePlugin, headless, CollisionLayer, CommonBullet,
+    Hitbox, Invader, InvaderDifficulty, OnGameScreen, PlayerBullet, Velocity, BULLET_SIZE,
+    INVADER_SIZE,
+};
+
+const BULLETS: usize = 3000;
+const INVADERS: usize = 3000;
+const ARENA: Rect = Rect {
+    min: Vec2::new(-440.0, -290.0),
+    max: Vec2::new(440.0, 290.0),
+};
+const TICKS: u32 = 60;
+
+fn random_point(rng: &mut ChaCha8Rng) -> Vec2 {
+    Vec2::new(
+        rng.gen_range(ARENA.min.x..ARENA.max.x),
+        rng.gen_range(ARENA.min.y..ARENA.max.y),
+    )
+}
+
+fn random_boxes(rng: &mut ChaCha8Rng) -> Vec<(Entity, Rect)> {
+    (0..BULLETS + INVADERS)
+        .map(|index| {
+            let size = if index < BULLETS {
+                BULLET_SIZE
+            } else {
+                INVADER_SIZE
+            };
+            let bounds = Rect::from_center_size(random_point(rng), size);
+            (Entity::from_raw(index as u32), bounds)
+        })
+        .collect()
+}
+
+fn time<T>(run: impl FnOnce() -> T) -> (T, Duration) {
+    let start = Instant::now();
+    let result = run();
+    (result, start.elapsed())
+}
+
+/// Overlapping pairs found by testing every box against every other one, against the grid
+fn bench_broad_phase(rng: &mut ChaCha8Rng) {
+    let boxes = random_boxes(rng);
+
+    let (all_pairs, all_pairs_time) = time(|| {
+        let mut pairs = 0;
+        for (index, (_, bounds)) in boxes.iter().enumerate() {
+            for (_, other_bounds) in &boxes[index + 1..] {
+                if !bounds.intersect(*other_bounds).is_empty() {
+                    pairs += 1;
+                }
+            }
+        }
+        pairs
+    });
+
+    let (grid_pairs, grid_time) = time(|| {
+        let mut grid = SpatialGrid::default();
+        for &(entity, bounds) in &boxes {
+            grid.insert(entity, bounds);
+        }
+        grid.pairs().len()
+    });
+
+    assert_eq!(all_pairs, grid_pairs);
+    println!(
+        "broad phase, {} boxes: every pair {:?}, spatial grid {:?} ({:.1}x faster), {} overlaps",
+        boxes.len(),
+        all_pairs_time,
+        grid_time,
+        all_pairs_time.as_secs_f64() / grid_time.as_secs_f64(),
+        grid_pairs
+    );
+}
+
+/// Whole simulation ticks of a round crowded with bullets and invaders
+fn bench_ticks(rng: &mut ChaCha8Rng) {
+    let mut app = App::new();
+    app.add_plugins(headless::HeadlessPlugins)
+        .insert_resource(CliArgs {
+            seed: Some(1),
+            ..default()
+        })
+        .insert_resource(headless::HeadlessRun::new(None))
+        .add_plugins(GamePlugin);
+    app.update();
+
+    // Invaders stay well above the player so the round doesn't end right away
+    for _ in 0..INVADERS {
+        let position = random_point(rng).max(Vec2::new(ARENA.min.x, 0.0));
+        app.world.spawn((
+            Invader {
+                difficulty: InvaderDifficulty::Easy,
+                column: 0,
+            },
+            Hitbox::aabb(CollisionLayer::Invader, INVADER_SIZE),
+            Transform::from_translation(position.extend(0.0)),
+            OnGameScreen,
+        ));
+    }
+    for _ in 0..BULLETS {
+        app.world.spawn((
+            CommonBullet,
+            PlayerBullet,
+            Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+            Velocity(Vec3::new(0.0, 500.0, 0.0)),
+            Transform::from_translation(random_point(rng).extend(0.0)),
+            OnGameScreen,
+        ));
+    }
+
+    let (_, ticks_time) = time(|| {
+        for _ in 0..TICKS {
+            app.update();
+        }
+    });
+
+    println!(
+        "{} ticks starting with {} bullets and {} invaders: {:?} per tick",
+        TICKS,
+        BULLETS,
+        INVADERS,
+        ticks_time / TICKS
+    );
+}
+
+fn main() {
+    let mut rng = ChaCha8Rng::seed_from_u64(0);
+    bench_broad_phase(&mut rng);
+    bench_ticks(&mut rng);
+}
@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+/// Handles to every sprite and sound used during a round.
+///
+/// Without an `AssetServer`, as in headless runs, the handles keep their defaults and the
+/// entities spawned with them are simply never drawn.
+#[derive(Resource, Default)]
+pub(crate) struct GameAssets {
+    pub(crate) player: Handle<Image>,
+    pub(crate) player_bullet: Handle<Image>,
+    pub(crate) green_invader: Handle<Image>,
+    pub(crate) yellow_invader: Handle<Image>,
+    pub(crate) red_invader: Handle<Image>,
+    pub(crate) green_bullet: Handle<Image>,
+    pub(crate) yellow_bullet: Handle<Image>,
+    pub(crate) red_bullet: Handle<Image>,
+    pub(crate) mystery_ship: Handle<Image>,
+    pub(crate) mystery_ship_sound: Handle<AudioSource>,
+    pub(crate) ambience: Handle<AudioSource>,
+    pub(crate) shoot_sound: Handle<AudioSource>,
+}
+
+pub(crate) fn load_game_assets(
+    asset_server: Res<AssetServer>,
+    mut game_assets: ResMut<GameAssets>,
+) {
+    *game_assets = GameAssets {
+        player: asset_server.load("player.png"),
+        player_bullet: asset_server.load("player-bullet.png"),
+        green_invader: asset_server.load("green.png"),
+        yellow_invader: asset_server.load("yellow.png"),
+        red_invader: asset_server.load("red.png"),
+        green_bullet: asset_server.load("green-bullet.png"),
+        yellow_bullet: asset_server.load("yellow-bullet.png"),
+        red_bullet: asset_server.load("red-bullet.png"),
+        mystery_ship: asset_server.load("extra.png"),
+        mystery_ship_sound: asset_server.load("mystery-ship.wav"),
+        ambience: asset_server.load("space-invaders-drum.wav"),
+        shoot_sound: asset_server.load("laser.wav"),
+    };
+}
@@ -1,20 +1,25 @@
 use crate::{
-    Collider, CommonBullet, Invader, InvaderBullet, InvaderBulletFiredEvent,
-    InvaderBulletHitPlayerEvent, Player, PlayerBullet, PlayerBulletFiredEvent, ShootSound,
-    Velocity, INVADER_SIZE, PLAYER_HEIGHT,
+    assets::GameAssets,
+    config::GameConfig,
+    interpolation::InterpolatedPosition,
+    player::{Dying, Invulnerable},
+    rng::GameRng,
+    CollisionEvent, CollisionLayer, CommonBullet, Hitbox, Invader, InvaderBullet,
+    InvaderBulletFiredEvent, InvaderBulletHitPlayerEvent, InvaderKilledEvent, OnGameScreen, Player,
+    PlayerBullet, PlayerBulletFiredEvent, Settings, Velocity, BULLET_SIZE,
 };
 use bevy::prelude::*;
 use rand::Rng;
 
-const BULLET_SPRITE_PATH: &str = "player-bullet.png";
-const PLAYER_BULLET_SPEED: f32 = 500.0;
-
 #[derive(Bundle)]
 pub(crate) struct PlayerBulletBundle {
     sprite_bundle: SpriteBundle,
     common_bullet: CommonBullet,
     bullet: PlayerBullet,
     velocity: Velocity,
+    hitbox: Hitbox,
+    interpolated_position: InterpolatedPosition,
+    on_game_screen: OnGameScreen,
 }
 
 #[derive(Bundle)]
@@ -23,17 +28,21 @@ pub(crate) struct InvaderBulletBundle {
     common_bullet: CommonBullet,
     bullet: InvaderBullet,
     velocity: Velocity,
+    hitbox: Hitbox,
+    interpolated_position: InterpolatedPosition,
+    on_game_screen: OnGameScreen,
 }
 
 pub(crate) fn spawn_player_bullet(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    config: Res<GameConfig>,
     mut player_bullet_fired_event: EventReader<PlayerBulletFiredEvent>,
 ) {
     for event in player_bullet_fired_event.read() {
         commands.spawn(PlayerBulletBundle {
             sprite_bundle: SpriteBundle {
-                texture: asset_server.load(BULLET_SPRITE_PATH),
+                texture: game_assets.player_bullet.clone(),
                 transform: Transform {
                     translation: event.0,
                     ..default()
@@ -42,20 +51,24 @@ pub(crate) fn spawn_player_bullet(
             },
             bullet: PlayerBullet,
             common_bullet: CommonBullet,
-            velocity: Velocity(Vec3::new(0.0, PLAYER_BULLET_SPEED, 0.0)),
+            velocity: Velocity(Vec3::new(0.0, config.player.bullet_speed, 0.0)),
+            hitbox: Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+            interpolated_position: InterpolatedPosition::new(event.0),
+            on_game_screen: OnGameScreen,
         });
     }
 }
 
 pub(crate) fn spawn_invader_bullet(
     mut commands: Commands,
-    asset_server: Res<AssetServer>,
+    game_assets: Res<GameAssets>,
+    config: Res<GameConfig>,
     mut invader_bullet_fired_event: EventReader<InvaderBulletFiredEvent>,
 ) {
     for event in invader_bullet_fired_event.read() {
         commands.spawn(InvaderBulletBundle {
             sprite_bundle: SpriteBundle {
-                texture: asset_server.load(event.invader_difficulty.get_bullet_sprite_path()),
+                texture: event.invader_difficulty.get_bullet_sprite(&game_assets),
                 transform: Transform {
                     translation: event.position,
                     ..default()
@@ -66,26 +79,36 @@ pub(crate) fn spawn_invader_bullet(
             common_bullet: CommonBullet,
             velocity: Velocity(Vec3::new(
                 0.0,
-                -event.invader_difficulty.get_bullet_speed(),
+                -config.invaders.bullet_speed(&event.invader_difficulty),
                 0.0,
             )),
+            hitbox: Hitbox::aabb(CollisionLayer::InvaderBullet, BULLET_SIZE),
+            interpolated_position: InterpolatedPosition::new(event.position),
+            on_game_screen: OnGameScreen,
         });
     }
 }
 
 pub(crate) fn player_bullet_sound(
     mut commands: Commands,
-    shoot_sound: Res<ShootSound>,
+    game_assets: Res<GameAssets>,
+    settings: Res<Settings>,
+    mut game_rng: ResMut<GameRng>,
     mut player_bullet_fired_event: EventReader<PlayerBulletFiredEvent>,
 ) {
+    if !settings.sound {
+        player_bullet_fired_event.clear();
+        return;
+    }
+
     for _event in player_bullet_fired_event.read() {
         commands.spawn(AudioBundle {
-            source: shoot_sound.0.clone(),
+            source: game_assets.shoot_sound.clone(),
             settings: PlaybackSettings {
                 mode: bevy::audio::PlaybackMode::Despawn,
                 speed: 2.0,
                 volume: bevy::audio::Volume::new_relative(
-                    0.1 + rand::thread_rng().gen_range(0.1..0.3),
+                    0.1 + game_rng.effects.gen_range(0.1..0.3),
                 ),
                 ..default()
             },
@@ -104,20 +127,13 @@ pub(crate) fn move_bullets(
 
 pub(crate) fn check_bullet_wall_collision(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<CommonBullet>>,
-    collider_query: Query<&Transform, With<Collider>>,
+    mut collision_events: EventReader<CollisionEvent>,
 ) {
-    for (bullet, bullet_transform) in bullet_query.iter() {
-        for collider_transform in collider_query.iter() {
-            if bevy::sprite::collide_aabb::collide(
-                bullet_transform.translation,
-                bullet_transform.scale.truncate(),
-                collider_transform.translation,
-                collider_transform.scale.truncate(),
-            )
-            .is_some()
-            {
-                commands.entity(bullet).despawn();
+    for event in collision_events.read() {
+        if let CollisionEvent::BulletHitWall { bullet } = *event {
+            // A bullet in a corner touches two walls at once
+            if let Some(mut bullet) = commands.get_entity(bullet) {
+                bullet.despawn();
             }
         }
     }
@@ -125,40 +141,64 @@ pub(crate) fn check_bullet_wall_collision(
 
 pub(crate) fn check_player_bullet_invader_collision(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<PlayerBullet>>,
-    invader_query: Query<(Entity, &Transform), With<Invader>>,
+    mut collision_events: EventReader<CollisionEvent>,
+    bullet_query: Query<(), With<PlayerBullet>>,
+    invader_query: Query<(&Invader, &Transform)>,
+    mut invader_killed_event: EventWriter<InvaderKilledEvent>,
 ) {
-    for (bullet, bullet_transform) in bullet_query.iter() {
-        for (invader, invader_transform) in invader_query.iter() {
-            if bullet_transform
-                .translation
-                .distance(invader_transform.translation)
-                < INVADER_SIZE
-            {
-                commands.entity(bullet).despawn();
-                commands.entity(invader).despawn();
-            }
+    // Each bullet kills one invader, and each invader can only be killed once, even if several
+    // of them overlap in the same tick
+    let mut spent_bullets = Vec::new();
+    let mut killed_invaders = Vec::new();
+
+    for event in collision_events.read() {
+        let CollisionEvent::PlayerBulletHitInvader { bullet, invader } = *event else {
+            continue;
+        };
+        // The bullet may already be gone, for instance into a wall
+        if spent_bullets.contains(&bullet)
+            || killed_invaders.contains(&invader)
+            || !bullet_query.contains(bullet)
+        {
+            continue;
         }
+        let Ok((invader_data, invader_transform)) = invader_query.get(invader) else {
+            continue;
+        };
+
+        commands.entity(bullet).despawn();
+        commands.entity(invader).despawn();
+        spent_bullets.push(bullet);
+        killed_invaders.push(invader);
+        invader_killed_event.send(InvaderKilledEvent {
+            position: invader_transform.translation,
+            invader_difficulty: invader_data.difficulty.clone(),
+        });
     }
 }
 
 pub(crate) fn check_invader_bullet_player_collision(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<InvaderBullet>>,
-    player_query: Query<&Transform, With<Player>>,
+    mut collision_events: EventReader<CollisionEvent>,
+    bullet_query: Query<(), With<InvaderBullet>>,
+    player_query: Query<(), (With<Player>, Without<Dying>, Without<Invulnerable>)>,
     mut invader_hit_player_event: EventWriter<InvaderBulletHitPlayerEvent>,
 ) {
-    let player_transform = player_query.single();
+    let hits: Vec<Entity> = collision_events
+        .read()
+        .filter_map(|event| match *event {
+            CollisionEvent::InvaderBulletHitPlayer { bullet, player }
+                if bullet_query.contains(bullet) && player_query.contains(player) =>
+            {
+                Some(bullet)
+            }
+            _ => None,
+        })
+        .collect();
 
-    for (bullet, bullet_transform) in bullet_query.iter() {
-        if bullet_transform
-            .translation
-            .distance(player_transform.translation)
-            < PLAYER_HEIGHT
-        {
-            commands.entity(bullet).despawn();
-            invader_hit_player_event.send(InvaderBulletHitPlayerEvent);
-            return;
-        }
+    // The player only dies once, other bullets hitting it in the same tick fly on
+    if let Some(&bullet) = hits.first() {
+        commands.entity(bullet).despawn();
+        invader_hit_player_event.send(InvaderBulletHitPlayerEvent);
     }
 }
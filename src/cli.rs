@@ -0,0 +1,82 @@
+use std::path::PathBuf;
+
+use bevy::prelude::*;
+
+pub const USAGE: &str = "Usage: space-invaders [--seed <number>] [--tick-rate <hz>] [--record <file>] [--replay <file>] [--headless [--ticks <count>]]";
+
+pub(crate) const DEFAULT_TICK_RATE: f64 = 60.0;
+
+/// Command line flags, inserted before the game plugin is added
+#[derive(Resource, Default, Debug, Clone)]
+pub struct CliArgs {
+    /// Seed for all gameplay randomness, a fresh one is picked every round when missing
+    pub seed: Option<u64>,
+    /// Simulation ticks per second
+    pub tick_rate: Option<f64>,
+    /// File every finished round is recorded to
+    pub record: Option<PathBuf>,
+    /// Replay file played back instead of reading the keyboard
+    pub replay: Option<PathBuf>,
+    /// Run the simulation without a window or sound, as fast as possible
+    pub headless: bool,
+    /// Number of simulation ticks after which a headless run stops
+    pub ticks: Option<u32>,
+    /// Print the usage instead of running the game, set by `--help`
+    pub help: bool,
+}
+
+impl CliArgs {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut cli_args = CliArgs::default();
+
+        while let Some(arg) = args.next() {
+            match arg.as_str() {
+                "--seed" => {
+                    let value = args.next().ok_or("--seed needs a value")?;
+                    let seed = value
+                        .parse()
+                        .map_err(|_| format!("invalid seed '{}'", value))?;
+                    cli_args.seed = Some(seed);
+                }
+                "--tick-rate" => {
+                    let value = args.next().ok_or("--tick-rate needs a value")?;
+                    let tick_rate = value
+                        .parse()
+                        .ok()
+                        .filter(|tick_rate: &f64| *tick_rate > 0.0)
+                        .ok_or_else(|| format!("invalid tick rate '{}'", value))?;
+                    cli_args.tick_rate = Some(tick_rate);
+                }
+                "--record" => {
+                    let value = args.next().ok_or("--record needs a file")?;
+                    cli_args.record = Some(value.into());
+                }
+                "--replay" => {
+                    let value = args.next().ok_or("--replay needs a file")?;
+                    cli_args.replay = Some(value.into());
+                }
+                "--headless" => cli_args.headless = true,
+                "--ticks" => {
+                    let value = args.next().ok_or("--ticks needs a value")?;
+                    let ticks = value
+                        .parse()
+                        .ok()
+                        .filter(|ticks: &u32| *ticks > 0)
+                        .ok_or_else(|| format!("invalid tick count '{}'", value))?;
+                    cli_args.ticks = Some(ticks);
+                }
+                "--help" | "-h" => {
+                    cli_args.help = true;
+                    return Ok(cli_args);
+                }
+                _ => return Err(format!("unknown argument '{}'\n{}", arg, USAGE)),
+            }
+        }
+
+        if cli_args.ticks.is_some() && !cli_args.headless {
+            return Err(format!("--ticks only works with --headless\n{}", USAGE));
+        }
+
+        Ok(cli_args)
+    }
+}
@@ -0,0 +1,396 @@
+use bevy::{prelude::*, utils::HashMap};
+
+use crate::{
+    player::{Dying, Invulnerable},
+    CollisionEvent, CollisionLayer, Hitbox, HitboxShape, Velocity,
+};
+
+/// Side of a spatial grid cell, a bit larger than an invader so most hitboxes cover few cells
+const GRID_CELL_SIZE: f32 = 64.0;
+
+/// Uniform spatial hash of the area every hitbox covered during the tick, rebuilt every tick
+/// before collisions are detected
+#[derive(Resource)]
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<usize>>,
+    entries: Vec<(Entity, Rect)>,
+}
+
+impl Default for SpatialGrid {
+    fn default() -> Self {
+        SpatialGrid::new(GRID_CELL_SIZE)
+    }
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        SpatialGrid {
+            cell_size,
+            cells: HashMap::default(),
+            entries: Vec::new(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.clear();
+        self.entries.clear();
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn insert(&mut self, entity: Entity, bounds: Rect) {
+        let index = self.entries.len();
+        self.entries.push((entity, bounds));
+
+        let (min, max) = self.cell_range(bounds);
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                self.cells.entry(IVec2::new(x, y)).or_default().push(index);
+            }
+        }
+    }
+
+    fn cell(&self, point: Vec2) -> IVec2 {
+        (point / self.cell_size).floor().as_ivec2()
+    }
+
+    fn cell_range(&self, bounds: Rect) -> (IVec2, IVec2) {
+        (self.cell(bounds.min), self.cell(bounds.max))
+    }
+
+    /// Every pair of entities whose bounds overlap, each reported once, in insertion order
+    pub fn pairs(&self) -> Vec<(Entity, Entity)> {
+        let mut pairs = Vec::new();
+
+        for (index, &(entity, bounds)) in self.entries.iter().enumerate() {
+            let (min, max) = self.cell_range(bounds);
+            for y in min.y..=max.y {
+                for x in min.x..=max.x {
+                    let cell = IVec2::new(x, y);
+                    for &other_index in &self.cells[&cell] {
+                        if other_index <= index {
+                            continue;
+                        }
+                        let (other, other_bounds) = self.entries[other_index];
+                        let overlap = bounds.intersect(other_bounds);
+                        // Boxes sharing several cells are only reported from the cell holding
+                        // the corner of their overlap
+                        if overlap.is_empty() || self.cell(overlap.min) != cell {
+                            continue;
+                        }
+                        pairs.push((entity, other));
+                    }
+                }
+            }
+        }
+
+        pairs
+    }
+}
+
+impl Hitbox {
+    fn center(&self, position: Vec3) -> Vec2 {
+        position.truncate() + self.offset
+    }
+
+    /// Smallest box around the hitbox placed at the given position
+    pub(crate) fn bounds(&self, position: Vec3) -> Rect {
+        let size = match self.shape {
+            HitboxShape::Aabb(size) => size,
+            HitboxShape::Circle { radius } => Vec2::splat(radius * 2.0),
+        };
+        Rect::from_center_size(self.center(position), size)
+    }
+
+    pub(crate) fn overlaps(&self, position: Vec3, other: &Hitbox, other_position: Vec3) -> bool {
+        let center = self.center(position);
+        let other_center = other.center(other_position);
+
+        match (self.shape, other.shape) {
+            (HitboxShape::Aabb(_), HitboxShape::Aabb(_)) => !self
+                .bounds(position)
+                .intersect(other.bounds(other_position))
+                .is_empty(),
+            (
+                HitboxShape::Circle { radius },
+                HitboxShape::Circle {
+                    radius: other_radius,
+                },
+            ) => center.distance(other_center) < radius + other_radius,
+            (HitboxShape::Aabb(size), HitboxShape::Circle { radius }) => {
+                circle_overlaps_box(other_center, radius, center, size)
+            }
+            (HitboxShape::Circle { radius }, HitboxShape::Aabb(size)) => {
+                circle_overlaps_box(center, radius, other_center, size)
+            }
+        }
+    }
+}
+
+fn circle_overlaps_box(circle_center: Vec2, radius: f32, box_center: Vec2, size: Vec2) -> bool {
+    let closest = circle_center.clamp(box_center - size / 2.0, box_center + size / 2.0);
+    closest.distance(circle_center) < radius
+}
+
+impl CollisionEvent {
+    /// The event for two overlapping entities, `None` when no rule cares about the pair
+    fn between(a: (Entity, CollisionLayer), b: (Entity, CollisionLayer)) -> Option<Self> {
+        use CollisionLayer::*;
+
+        let ((first, first_layer), (second, second_layer)) =
+            if a.1 <= b.1 { (a, b) } else { (b, a) };
+
+        match (first_layer, second_layer) {
+            (Player, InvaderBullet) => Some(CollisionEvent::InvaderBulletHitPlayer {
+                bullet: second,
+                player: first,
+            }),
+            (Invader, PlayerBullet) => Some(CollisionEvent::PlayerBulletHitInvader {
+                bullet: second,
+                invader: first,
+            }),
+            (Invader, Shield) => Some(CollisionEvent::InvaderHitShield {
+                invader: first,
+                shield: second,
+            }),
+            (Invader, Wall) => Some(CollisionEvent::InvaderHitWall { invader: first }),
+            (MysteryShip, PlayerBullet) => Some(CollisionEvent::PlayerBulletHitMysteryShip {
+                bullet: second,
+                mystery_ship: first,
+            }),
+            (PlayerBullet | InvaderBullet, Shield) => Some(CollisionEvent::BulletHitShield {
+                bullet: first,
+                shield: second,
+            }),
+            (PlayerBullet | InvaderBullet, Wall) => {
+                Some(CollisionEvent::BulletHitWall { bullet: first })
+            }
+            _ => None,
+        }
+    }
+
+    /// The bullet involved in the collision, if any
+    fn bullet(&self) -> Option<Entity> {
+        match *self {
+            CollisionEvent::PlayerBulletHitInvader { bullet, .. }
+            | CollisionEvent::PlayerBulletHitMysteryShip { bullet, .. }
+            | CollisionEvent::InvaderBulletHitPlayer { bullet, .. }
+            | CollisionEvent::BulletHitShield { bullet, .. }
+            | CollisionEvent::BulletHitWall { bullet } => Some(bullet),
+            CollisionEvent::InvaderHitShield { .. } | CollisionEvent::InvaderHitWall { .. } => None,
+        }
+    }
+
+    /// Bullets fly on through holes in shields, anything else stops them
+    fn stops_bullet(&self) -> bool {
+        !matches!(self, CollisionEvent::BulletHitShield { .. })
+    }
+
+    /// The target of a hit, for targets that take at most one bullet per tick
+    fn target(&self) -> Option<Entity> {
+        match *self {
+            CollisionEvent::PlayerBulletHitInvader { invader, .. } => Some(invader),
+            CollisionEvent::PlayerBulletHitMysteryShip { mystery_ship, .. } => Some(mystery_ship),
+            CollisionEvent::InvaderBulletHitPlayer { player, .. } => Some(player),
+            _ => None,
+        }
+    }
+}
+
+/// Earliest time in [0, 1] at which a point moving from `start` to `end` is inside the box of
+/// the given half size around the origin
+fn segment_box_entry(start: Vec2, end: Vec2, half_size: Vec2) -> Option<f32> {
+    let delta = end - start;
+    let mut entry: f32 = 0.0;
+    let mut exit: f32 = 1.0;
+
+    for axis in 0..2 {
+        if delta[axis] == 0.0 {
+            if start[axis].abs() >= half_size[axis] {
+                return None;
+            }
+            continue;
+        }
+
+        let near = (-half_size[axis] - start[axis]) / delta[axis];
+        let far = (half_size[axis] - start[axis]) / delta[axis];
+        entry = entry.max(near.min(far));
+        exit = exit.min(near.max(far));
+    }
+
+    (entry < exit).then_some(entry)
+}
+
+/// Earliest time in [0, 1] at which a point moving from `start` to `end` is inside the circle of
+/// the given radius around the origin
+fn segment_circle_entry(start: Vec2, end: Vec2, radius: f32) -> Option<f32> {
+    if start.length() < radius {
+        return Some(0.0);
+    }
+
+    let delta = end - start;
+    let a = delta.length_squared();
+    if a == 0.0 {
+        return None;
+    }
+    let b = 2.0 * start.dot(delta);
+    let c = start.length_squared() - radius * radius;
+    let discriminant = b * b - 4.0 * a * c;
+    if discriminant <= 0.0 {
+        return None;
+    }
+
+    let entry = (-b - discriminant.sqrt()) / (2.0 * a);
+    (0.0..=1.0).contains(&entry).then_some(entry)
+}
+
+/// A hitbox at the end of the tick along with how far it moved during the tick
+struct SweptHitbox<'a> {
+    hitbox: &'a Hitbox,
+    position: Vec3,
+    displacement: Vec2,
+}
+
+impl<'a> SweptHitbox<'a> {
+    fn new(
+        transform: &Transform,
+        hitbox: &'a Hitbox,
+        velocity: Option<&Velocity>,
+        time: &Time,
+    ) -> Self {
+        SweptHitbox {
+            hitbox,
+            position: transform.translation,
+            displacement: velocity.map_or(Vec2::ZERO, |velocity| {
+                velocity.0.truncate() * time.delta_seconds()
+            }),
+        }
+    }
+
+    /// Covers the hitbox at both ends of its move
+    fn bounds(&self) -> Rect {
+        let start = self.position - self.displacement.extend(0.0);
+        self.hitbox
+            .bounds(self.position)
+            .union(self.hitbox.bounds(start))
+    }
+
+    /// Fraction of the tick after which the two hitboxes first touch, `None` when they never do
+    fn time_of_impact(&self, other: &SweptHitbox) -> Option<f32> {
+        if self.displacement == Vec2::ZERO
+            && other.displacement == Vec2::ZERO
+            && self
+                .hitbox
+                .overlaps(self.position, other.hitbox, other.position)
+        {
+            return Some(0.0);
+        }
+
+        // Seen from the other hitbox, only this one moves
+        let end = self.hitbox.center(self.position) - other.hitbox.center(other.position);
+        let start = end - (self.displacement - other.displacement);
+
+        match (self.hitbox.shape, other.hitbox.shape) {
+            (
+                HitboxShape::Circle { radius },
+                HitboxShape::Circle {
+                    radius: other_radius,
+                },
+            ) => segment_circle_entry(start, end, radius + other_radius),
+            // Boxes are swept exactly, a circle moving against a box is swept as its bounding
+            // box
+            _ => {
+                let half_size = (self.hitbox.bounds(Vec3::ZERO).size()
+                    + other.hitbox.bounds(Vec3::ZERO).size())
+                    / 2.0;
+                segment_box_entry(start, end, half_size)
+            }
+        }
+    }
+}
+
+pub(crate) fn rebuild_spatial_grid(
+    mut grid: ResMut<SpatialGrid>,
+    hitbox_query: Query<(Entity, &Transform, &Hitbox, Option<&Velocity>)>,
+    time: Res<Time>,
+) {
+    let mut hitboxes: Vec<(Entity, Rect)> = hitbox_query
+        .iter()
+        .map(|(entity, transform, hitbox, velocity)| {
+            let swept = SweptHitbox::new(transform, hitbox, velocity, &time);
+            (entity, swept.bounds())
+        })
+        .collect();
+    // Inserting in entity order makes the events come out in the same order on every run
+    hitboxes.sort_by_key(|(entity, _)| *entity);
+
+    grid.clear();
+    for (entity, bounds) in hitboxes {
+        grid.insert(entity, bounds);
+    }
+}
+
+/// Finds every pair of hitboxes that touched during the tick, testing the whole distance moving
+/// hitboxes travelled so fast ones can't skip over anything.
+///
+/// Only the pairs sharing a cell of the spatial grid are tested against each other. Events are
+/// sent in the order the hits happened, and nothing behind the first target that stops a bullet
+/// is reported for it.
+pub(crate) fn detect_collisions(
+    grid: Res<SpatialGrid>,
+    hitbox_query: Query<(&Transform, &Hitbox, Option<&Velocity>)>,
+    untouchable_query: Query<(), Or<(With<Dying>, With<Invulnerable>)>>,
+    mut collision_events: EventWriter<CollisionEvent>,
+    time: Res<Time>,
+) {
+    let mut hits: Vec<(f32, CollisionEvent)> = Vec::new();
+    for (entity, other_entity) in grid.pairs() {
+        let Ok((transform, hitbox, velocity)) = hitbox_query.get(entity) else {
+            continue;
+        };
+        let Ok((other_transform, other_hitbox, other_velocity)) = hitbox_query.get(other_entity)
+        else {
+            continue;
+        };
+
+        let Some(event) =
+            CollisionEvent::between((entity, hitbox.layer), (other_entity, other_hitbox.layer))
+        else {
+            continue;
+        };
+        let swept = SweptHitbox::new(transform, hitbox, velocity, &time);
+        let other = SweptHitbox::new(other_transform, other_hitbox, other_velocity, &time);
+        if let Some(time_of_impact) = swept.time_of_impact(&other) {
+            hits.push((time_of_impact, event));
+        }
+    }
+    // The sort is stable, hits at the same time keep the order of the grid
+    hits.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+    let mut stopped_bullets = Vec::new();
+    let mut struck_targets = Vec::new();
+    for (_, event) in hits {
+        if let Some(bullet) = event.bullet() {
+            if stopped_bullets.contains(&bullet) {
+                continue;
+            }
+            // The rules ignore hits on a player who can't be hit and on a target already struck
+            // by an earlier bullet, such a bullet flies on to whatever is behind
+            let target_takes_hit = event.target().map_or(true, |target| {
+                !struck_targets.contains(&target) && !untouchable_query.contains(target)
+            });
+            if event.stops_bullet() && target_takes_hit {
+                stopped_bullets.push(bullet);
+                struck_targets.extend(event.target());
+            }
+        }
+        collision_events.send(event);
+    }
+}
@@ -0,0 +1,358 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{invaders::ShotPattern, InvaderDifficulty};
+
+const CONFIG_FILE_NAME: &str = "config.ron";
+const RELOAD_POLL_INTERVAL: f32 = 0.5;
+
+/// Gameplay tuning values, read from `assets/config.ron`.
+///
+/// Fields left out of the file keep their default value, unknown fields are rejected so typos
+/// don't go unnoticed.
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, Default, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct GameConfig {
+    pub player: PlayerTuning,
+    pub invaders: InvaderTuning,
+    pub invader_fire: InvaderFireTuning,
+    pub mystery_ship: MysteryShipTuning,
+    pub shields: ShieldTuning,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct PlayerTuning {
+    pub speed: f32,
+    pub bullet_speed: f32,
+    /// Minimum time between two shots
+    pub fire_rate: f32,
+    pub death_animation_time: f32,
+    pub respawn_delay: f32,
+    pub invulnerability_time: f32,
+}
+
+impl Default for PlayerTuning {
+    fn default() -> Self {
+        PlayerTuning {
+            speed: 500.0,
+            bullet_speed: 500.0,
+            fire_rate: 0.2,
+            death_animation_time: 1.5,
+            respawn_delay: 1.0,
+            invulnerability_time: 2.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct InvaderTuning {
+    /// Time between steps of a full formation, unless the level sets its own
+    pub movement_rate: f32,
+    /// Distance covered by one step
+    pub movement: f32,
+    pub move_down_amount: f32,
+    /// Step interval factor applied every time the formation moves down
+    pub descent_speedup: f32,
+    /// Step interval factor applied once per wave, to the fastest level rate played so far
+    pub wave_speedup: f32,
+    /// Shortest time between steps, however many speedups add up
+    pub min_movement_rate: f32,
+    /// How much lower each wave starts than the previous one
+    pub wave_start_drop: f32,
+    pub max_wave_start_drop: f32,
+    /// Shape of the speedup as the formation thins out, see `InvaderTuning::speed_factor`
+    pub speed_curve_exponent: f32,
+    pub speed_curve_min_factor: f32,
+    pub easy_bullet_speed: f32,
+    pub medium_bullet_speed: f32,
+    pub hard_bullet_speed: f32,
+}
+
+impl Default for InvaderTuning {
+    fn default() -> Self {
+        InvaderTuning {
+            movement_rate: 0.1,
+            movement: 5.0,
+            move_down_amount: 15.0,
+            descent_speedup: 0.8,
+            wave_speedup: 0.85,
+            min_movement_rate: 0.02,
+            wave_start_drop: 20.0,
+            max_wave_start_drop: 120.0,
+            speed_curve_exponent: 1.0,
+            speed_curve_min_factor: 0.05,
+            easy_bullet_speed: 200.0,
+            medium_bullet_speed: 350.0,
+            hard_bullet_speed: 500.0,
+        }
+    }
+}
+
+impl InvaderTuning {
+    /// Scales the step interval by `min_factor + (1 - min_factor) * remaining_fraction ^ exponent`,
+    /// so a full formation marches at the wave speed and the last invader at `min_factor` of it
+    pub(crate) fn speed_factor(&self, remaining: usize, formation_size: usize) -> f32 {
+        let remaining_fraction = (remaining as f32 / formation_size.max(1) as f32).clamp(0.0, 1.0);
+        self.speed_curve_min_factor
+            + (1.0 - self.speed_curve_min_factor)
+                * remaining_fraction.powf(self.speed_curve_exponent)
+    }
+
+    pub(crate) fn bullet_speed(&self, difficulty: &InvaderDifficulty) -> f32 {
+        match difficulty {
+            InvaderDifficulty::Easy => self.easy_bullet_speed,
+            InvaderDifficulty::Medium => self.medium_bullet_speed,
+            InvaderDifficulty::Hard => self.hard_bullet_speed,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct InvaderFireTuning {
+    pub shot_interval: f32,
+    pub max_bullets: usize,
+    /// Patterns used in turn, one per shot
+    pub patterns: Vec<ShotPattern>,
+}
+
+impl Default for InvaderFireTuning {
+    fn default() -> Self {
+        InvaderFireTuning {
+            shot_interval: 0.8,
+            max_bullets: 3,
+            patterns: vec![
+                ShotPattern::ColumnAbovePlayer,
+                ShotPattern::RollingSequence,
+                ShotPattern::RandomColumn,
+            ],
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct MysteryShipTuning {
+    pub speed: f32,
+    pub min_spawn_interval: f32,
+    pub max_spawn_interval: f32,
+}
+
+impl Default for MysteryShipTuning {
+    fn default() -> Self {
+        MysteryShipTuning {
+            speed: 150.0,
+            min_spawn_interval: 15.0,
+            max_spawn_interval: 25.0,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(default, deny_unknown_fields)]
+pub struct ShieldTuning {
+    /// Width and height of the crater a bullet carves out of a shield
+    pub crater_size: f32,
+}
+
+impl Default for ShieldTuning {
+    fn default() -> Self {
+        ShieldTuning { crater_size: 12.0 }
+    }
+}
+
+impl GameConfig {
+    pub fn from_ron(contents: &str) -> Result<Self, String> {
+        let config: GameConfig = ron::from_str(contents).map_err(|error| error.to_string())?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    /// Reads the config file, `None` means there is no file and the defaults apply
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        match fs::read_to_string(path) {
+            Ok(contents) => GameConfig::from_ron(&contents).map(Some),
+            Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error.to_string()),
+        }
+    }
+
+    /// Lists every value that is out of range, one per line
+    fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        let positive = [
+            ("player.speed", self.player.speed),
+            ("player.bullet_speed", self.player.bullet_speed),
+            ("player.fire_rate", self.player.fire_rate),
+            (
+                "player.death_animation_time",
+                self.player.death_animation_time,
+            ),
+            ("player.respawn_delay", self.player.respawn_delay),
+            (
+                "player.invulnerability_time",
+                self.player.invulnerability_time,
+            ),
+            ("invaders.movement_rate", self.invaders.movement_rate),
+            ("invaders.movement", self.invaders.movement),
+            ("invaders.move_down_amount", self.invaders.move_down_amount),
+            (
+                "invaders.min_movement_rate",
+                self.invaders.min_movement_rate,
+            ),
+            (
+                "invaders.speed_curve_exponent",
+                self.invaders.speed_curve_exponent,
+            ),
+            (
+                "invaders.easy_bullet_speed",
+                self.invaders.easy_bullet_speed,
+            ),
+            (
+                "invaders.medium_bullet_speed",
+                self.invaders.medium_bullet_speed,
+            ),
+            (
+                "invaders.hard_bullet_speed",
+                self.invaders.hard_bullet_speed,
+            ),
+            (
+                "invader_fire.shot_interval",
+                self.invader_fire.shot_interval,
+            ),
+            ("mystery_ship.speed", self.mystery_ship.speed),
+            (
+                "mystery_ship.min_spawn_interval",
+                self.mystery_ship.min_spawn_interval,
+            ),
+            ("shields.crater_size", self.shields.crater_size),
+        ];
+        for (name, value) in positive {
+            // Written so that NaN fails as well
+            if value.is_nan() || value <= 0.0 {
+                errors.push(format!("{} must be greater than 0, got {}", name, value));
+            }
+        }
+
+        let non_negative = [
+            ("invaders.wave_start_drop", self.invaders.wave_start_drop),
+            (
+                "invaders.max_wave_start_drop",
+                self.invaders.max_wave_start_drop,
+            ),
+        ];
+        for (name, value) in non_negative {
+            if value.is_nan() || value < 0.0 {
+                errors.push(format!("{} can't be negative, got {}", name, value));
+            }
+        }
+
+        let factors = [
+            ("invaders.descent_speedup", self.invaders.descent_speedup),
+            ("invaders.wave_speedup", self.invaders.wave_speedup),
+            (
+                "invaders.speed_curve_min_factor",
+                self.invaders.speed_curve_min_factor,
+            ),
+        ];
+        for (name, value) in factors {
+            if value.is_nan() || value <= 0.0 || value > 1.0 {
+                errors.push(format!("{} must be in (0, 1], got {}", name, value));
+            }
+        }
+
+        if self.invaders.min_movement_rate > self.invaders.movement_rate {
+            errors.push(format!(
+                "invaders.min_movement_rate ({}) can't be above invaders.movement_rate ({})",
+                self.invaders.min_movement_rate, self.invaders.movement_rate
+            ));
+        }
+        if self.mystery_ship.min_spawn_interval >= self.mystery_ship.max_spawn_interval {
+            errors.push(format!(
+                "mystery_ship.min_spawn_interval ({}) must be below max_spawn_interval ({})",
+                self.mystery_ship.min_spawn_interval, self.mystery_ship.max_spawn_interval
+            ));
+        }
+        if self.invader_fire.patterns.is_empty() {
+            errors.push("invader_fire.patterns needs at least one pattern".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+pub fn config_path() -> PathBuf {
+    FileAssetReader::get_base_path()
+        .join("assets")
+        .join(CONFIG_FILE_NAME)
+}
+
+/// Present when the config file is reloaded as soon as it changes on disk
+#[derive(Resource)]
+pub struct ConfigWatcher {
+    path: PathBuf,
+    last_modified: Option<SystemTime>,
+    poll_timer: Timer,
+}
+
+impl ConfigWatcher {
+    pub fn new(path: PathBuf) -> Self {
+        let last_modified = modified_time(&path);
+
+        ConfigWatcher {
+            path,
+            last_modified,
+            poll_timer: Timer::from_seconds(RELOAD_POLL_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+pub(crate) fn reload_config(
+    mut watcher: ResMut<ConfigWatcher>,
+    mut config: ResMut<GameConfig>,
+    time: Res<Time<Real>>,
+) {
+    if !watcher.poll_timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let modified = modified_time(&watcher.path);
+    if modified.is_none() || modified == watcher.last_modified {
+        return;
+    }
+    watcher.last_modified = modified;
+
+    match GameConfig::load(&watcher.path) {
+        Ok(Some(new_config)) => {
+            if *config != new_config {
+                *config = new_config;
+                info!("Reloaded game config from {}", watcher.path.display());
+            }
+        }
+        Ok(None) => {}
+        Err(error) => error!(
+            "Keeping the previous game config, {} is invalid:\n{}",
+            watcher.path.display(),
+            error
+        ),
+    }
+}
@@ -0,0 +1,184 @@
+use std::{
+    collections::BTreeMap,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::data_files::{self, VersionedFile};
+
+const CONTROLS_FILE_NAME: &str = "controls.ron";
+
+/// Something the player does with the keyboard, keys are bound to actions rather than read
+/// directly
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Action {
+    MoveLeft,
+    MoveRight,
+    Fire,
+    Pause,
+}
+
+impl Action {
+    pub const ALL: [Action; 4] = [
+        Action::MoveLeft,
+        Action::MoveRight,
+        Action::Fire,
+        Action::Pause,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            Action::MoveLeft => "Move Left",
+            Action::MoveRight => "Move Right",
+            Action::Fire => "Fire",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+/// The keys bound to every action, a key triggers at most one action
+#[derive(Resource, Clone, Debug, PartialEq)]
+pub struct InputMap {
+    bindings: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl Default for InputMap {
+    fn default() -> Self {
+        InputMap {
+            bindings: BTreeMap::from([
+                (Action::MoveLeft, vec![KeyCode::Left, KeyCode::A]),
+                (Action::MoveRight, vec![KeyCode::Right, KeyCode::D]),
+                (Action::Fire, vec![KeyCode::Space]),
+                (Action::Pause, vec![KeyCode::Escape, KeyCode::P]),
+            ]),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize)]
+struct ControlsFile {
+    version: u32,
+    bindings: BTreeMap<Action, Vec<KeyCode>>,
+}
+
+impl VersionedFile for ControlsFile {
+    const VERSION: u32 = 1;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl InputMap {
+    pub fn keys(&self, action: Action) -> &[KeyCode] {
+        self.bindings.get(&action).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn pressed(&self, action: Action, keyboard_input: &Input<KeyCode>) -> bool {
+        keyboard_input.any_pressed(self.keys(action).iter().copied())
+    }
+
+    pub fn just_pressed(&self, action: Action, keyboard_input: &Input<KeyCode>) -> bool {
+        keyboard_input.any_just_pressed(self.keys(action).iter().copied())
+    }
+
+    /// The action the key is bound to, if any
+    pub fn action(&self, key_code: KeyCode) -> Option<Action> {
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.contains(&key_code))
+            .map(|(action, _)| *action)
+    }
+
+    /// Binds the key to the action in place of its current keys. A key already bound to another
+    /// action is refused, the error is that action.
+    pub fn bind(&mut self, action: Action, key_code: KeyCode) -> Result<(), Action> {
+        match self.action(key_code) {
+            Some(other) if other != action => Err(other),
+            _ => {
+                self.bindings.insert(action, vec![key_code]);
+                Ok(())
+            }
+        }
+    }
+
+    pub fn from_ron(contents: &str) -> Result<Self, String> {
+        let file: ControlsFile = data_files::from_ron(contents)?;
+        let input_map = InputMap {
+            bindings: file.bindings,
+        };
+        input_map.validate()?;
+        Ok(input_map)
+    }
+
+    pub fn to_ron(&self) -> Result<String, ron::Error> {
+        data_files::to_ron(&ControlsFile {
+            version: ControlsFile::VERSION,
+            bindings: self.bindings.clone(),
+        })
+    }
+
+    /// Reads the controls file, `None` means there is no file and the default keys apply
+    pub fn load(path: &Path) -> Result<Option<Self>, String> {
+        data_files::read(path)?
+            .map(|contents| InputMap::from_ron(&contents))
+            .transpose()
+    }
+
+    /// Every action needs a key, and no key may trigger two of them
+    fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        for action in Action::ALL {
+            if self.keys(action).is_empty() {
+                errors.push(format!("{:?} has no key", action));
+            }
+        }
+        for (action, keys) in &self.bindings {
+            for key_code in keys {
+                let Some(first_action) = self.action(*key_code) else {
+                    continue;
+                };
+                if first_action != *action {
+                    errors.push(format!(
+                        "{:?} is bound to both {:?} and {:?}",
+                        key_code, first_action, action
+                    ));
+                }
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+}
+
+pub fn controls_path() -> Option<PathBuf> {
+    data_files::data_path(CONTROLS_FILE_NAME)
+}
+
+/// Present when changed bindings are written to a controls file, otherwise they only last until
+/// the game is closed
+#[derive(Resource)]
+pub struct ControlsPath(pub PathBuf);
+
+pub(crate) fn save_controls(input_map: Res<InputMap>, controls_path: Res<ControlsPath>) {
+    if !input_map.is_changed() || input_map.is_added() {
+        return;
+    }
+
+    let path = &controls_path.0;
+    let result = input_map
+        .to_ron()
+        .map_err(|error| error.to_string())
+        .and_then(|contents| data_files::write(path, &contents));
+
+    if let Err(error) = result {
+        error!("Could not save controls to {}: {}", path.display(), error);
+    }
+}
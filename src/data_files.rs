@@ -0,0 +1,50 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// On-disk representation of something the game keeps between runs. The version is bumped
+/// whenever the layout changes, files of any other version are refused.
+pub(crate) trait VersionedFile: Serialize + DeserializeOwned {
+    const VERSION: u32;
+
+    fn version(&self) -> u32;
+}
+
+pub(crate) fn from_ron<F: VersionedFile>(contents: &str) -> Result<F, String> {
+    let file: F = ron::from_str(contents).map_err(|error| error.to_string())?;
+
+    if file.version() != F::VERSION {
+        return Err(format!("unsupported version {}", file.version()));
+    }
+
+    Ok(file)
+}
+
+pub(crate) fn to_ron<F: VersionedFile>(file: &F) -> Result<String, ron::Error> {
+    ron::ser::to_string_pretty(file, ron::ser::PrettyConfig::default())
+}
+
+/// Where a file of the game goes in the platform's data directory, if there is one
+pub(crate) fn data_path(file_name: &str) -> Option<PathBuf> {
+    dirs::data_dir().map(|data_dir| data_dir.join(env!("CARGO_PKG_NAME")).join(file_name))
+}
+
+/// Reads a file, `None` means there is no such file yet
+pub(crate) fn read(path: &Path) -> Result<Option<String>, String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(error.to_string()),
+    }
+}
+
+/// Writes a file, creating its directory first
+pub(crate) fn write(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(directory) = path.parent() {
+        fs::create_dir_all(directory).map_err(|error| error.to_string())?;
+    }
+    fs::write(path, contents).map_err(|error| error.to_string())
+}
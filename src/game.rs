@@ -1,67 +1,349 @@
-use bevy::prelude::*;
+use bevy::{prelude::*, time::TimeUpdateStrategy};
 
 use crate::{
-    bullets, gamestate, invaders, player, shields, walls, InvaderBulletFiredEvent,
-    InvaderBulletHitPlayerEvent, InvadersReachedBottomEvent, PlayerBulletFiredEvent,
-    PlayerKilledAllInvadersEvent, ShootSound,
+    assets, bullets, cli, collision, config, controls, gamestate, headless, highscores, hud, input,
+    interpolation, invaders, levels, menu, mystery_ship, pause, player, postgame, replay, rng,
+    score, shields, turns, walls, waves, Ambience, CollisionEvent, HiScore,
+    InvaderBulletFiredEvent, InvaderBulletHitPlayerEvent, InvaderKilledEvent,
+    InvadersReachedBottomEvent, MysteryShip, MysteryShipHitEvent, OnGameScreen,
+    PlayerBulletFiredEvent, PlayerKilledAllInvadersEvent, PlayerOutOfLivesEvent, Settings,
+    TurnOverEvent,
 };
 
 pub struct GamePlugin;
 
+/// Ordering of every fixed simulation tick
+#[derive(SystemSet, Debug, Clone, PartialEq, Eq, Hash)]
+pub(crate) enum SimulationSet {
+    RestorePositions,
+    Gameplay,
+    SavePositions,
+}
+
 impl Plugin for GamePlugin {
     fn build(&self, app: &mut App) {
-        app.add_state::<GameState>()
+        let tick_rate = app
+            .world
+            .get_resource::<cli::CliArgs>()
+            .and_then(|cli_args| cli_args.tick_rate)
+            .unwrap_or(cli::DEFAULT_TICK_RATE);
+
+        let fixed_time = Time::<Fixed>::from_hz(tick_rate);
+        let headless = app.world.contains_resource::<headless::HeadlessRun>();
+
+        app.add_state::<GameState>();
+
+        // Replays and headless runs skip the menu and start playing right away
+        if headless || app.world.contains_resource::<replay::ReplayPlayback>() {
+            app.insert_resource(State::new(GameState::Game));
+        }
+
+        // Without a window nothing needs to wait for real time, every frame runs exactly one tick
+        if headless {
+            app.insert_resource(TimeUpdateStrategy::ManualDuration(fixed_time.timestep()))
+                .add_systems(
+                    FixedUpdate,
+                    headless::count_tick.in_set(SimulationSet::Gameplay),
+                )
+                .add_systems(
+                    Update,
+                    headless::stop_at_tick_limit
+                        .run_if(not(resource_exists::<gamestate::GameResult>())),
+                )
+                .add_systems(
+                    OnEnter(GameState::PostGame),
+                    headless::stop_at_game_over.after(replay::check_playback),
+                );
+        }
+
+        app.insert_resource(fixed_time)
+            .configure_sets(
+                FixedUpdate,
+                (
+                    SimulationSet::RestorePositions,
+                    SimulationSet::Gameplay.run_if(
+                        in_state(GameState::Game)
+                            .and_then(not(resource_exists::<gamestate::GameResult>())),
+                    ),
+                    SimulationSet::SavePositions,
+                )
+                    .chain(),
+            )
+            .add_systems(
+                FixedUpdate,
+                (
+                    interpolation::restore_positions.in_set(SimulationSet::RestorePositions),
+                    interpolation::save_positions.in_set(SimulationSet::SavePositions),
+                ),
+            )
+            .add_systems(
+                PostUpdate,
+                interpolation::interpolate_positions
+                    .before(bevy::transform::TransformSystem::TransformPropagate),
+            )
+            .add_state::<menu::MenuState>()
+            .add_state::<pause::PauseState>()
+            .init_resource::<cli::CliArgs>()
+            .init_resource::<rng::GameRng>()
+            .init_resource::<Settings>()
+            .init_resource::<assets::GameAssets>()
+            .init_resource::<input::PlayerInput>()
+            .init_resource::<input::GamepadSlots>()
+            .init_resource::<controls::InputMap>()
+            .init_resource::<player::LivesConfig>()
+            .init_resource::<turns::PlayersConfig>()
+            .init_resource::<HiScore>()
+            .init_resource::<waves::WaveConfig>()
+            .init_resource::<config::GameConfig>()
+            .init_resource::<levels::LevelSequence>()
+            .init_resource::<collision::SpatialGrid>()
+            .add_event::<CollisionEvent>()
             .add_event::<InvaderBulletFiredEvent>()
             .add_event::<PlayerBulletFiredEvent>()
+            .add_event::<InvaderKilledEvent>()
+            .add_event::<MysteryShipHitEvent>()
             .add_event::<InvadersReachedBottomEvent>()
             .add_event::<InvaderBulletHitPlayerEvent>()
             .add_event::<PlayerKilledAllInvadersEvent>()
+            .add_event::<PlayerOutOfLivesEvent>()
+            .add_event::<TurnOverEvent>()
             .add_systems(
                 Startup,
                 (
                     spawn_camera,
-                    setup_sound,
+                    assets::load_game_assets.run_if(resource_exists::<AssetServer>()),
+                    setup_sound
+                        .after(assets::load_game_assets)
+                        .run_if(resource_exists::<Assets<AudioSource>>()),
                     walls::spawn_walls,
+                    highscores::load_high_scores,
+                    replay::apply_replay_settings
+                        .run_if(resource_exists::<replay::ReplayPlayback>()),
+                ),
+            )
+            .add_systems(OnEnter(GameState::MainMenu), menu::enter_main_menu)
+            .add_systems(OnExit(GameState::MainMenu), menu::exit_menu)
+            .add_systems(OnEnter(menu::MenuState::Main), menu::spawn_main_menu)
+            .add_systems(
+                OnExit(menu::MenuState::Main),
+                despawn_screen::<menu::OnMainMenuScreen>,
+            )
+            .add_systems(OnEnter(menu::MenuState::Options), menu::spawn_options_menu)
+            .add_systems(
+                OnExit(menu::MenuState::Options),
+                despawn_screen::<menu::OnOptionsScreen>,
+            )
+            .add_systems(
+                OnEnter(menu::MenuState::HighScores),
+                menu::spawn_high_scores_menu,
+            )
+            .add_systems(
+                OnExit(menu::MenuState::HighScores),
+                despawn_screen::<menu::OnHighScoresScreen>,
+            )
+            .add_systems(
+                OnEnter(menu::MenuState::Controls),
+                menu::spawn_controls_menu,
+            )
+            .add_systems(
+                OnExit(menu::MenuState::Controls),
+                (
+                    despawn_screen::<menu::OnControlsScreen>,
+                    menu::stop_awaiting_key,
+                ),
+            )
+            .add_systems(OnEnter(menu::MenuState::Pause), menu::spawn_pause_menu)
+            .add_systems(
+                OnExit(menu::MenuState::Pause),
+                despawn_screen::<menu::OnPauseScreen>,
+            )
+            .add_systems(
+                Update,
+                (
+                    menu::button_colors,
+                    menu::menu_action,
+                    menu::update_setting_texts,
+                    menu::capture_binding.run_if(resource_exists::<menu::AwaitingKey>()),
+                    menu::update_binding_texts,
+                )
+                    .run_if(
+                        in_state(GameState::MainMenu).or_else(in_state(pause::PauseState::Paused)),
+                    ),
+            )
+            .add_systems(OnEnter(pause::PauseState::Paused), pause::pause_game)
+            .add_systems(OnExit(pause::PauseState::Paused), pause::resume_game)
+            .add_systems(
+                Update,
+                (
+                    // Keys pressed to rebind an action don't pause or resume the game
+                    pause::toggle_pause
+                        .before(menu::capture_binding)
+                        .run_if(in_state(GameState::Game))
+                        .run_if(not(resource_exists::<menu::AwaitingKey>())),
+                    pause::restart_round
+                        .after(menu::menu_action)
+                        .run_if(resource_exists::<pause::RestartRound>()),
+                ),
+            )
+            .add_systems(
+                OnTransition {
+                    from: GameState::Game,
+                    to: GameState::MainMenu,
+                },
+                despawn_screen::<OnGameScreen>,
+            )
+            .add_systems(Update, input::assign_gamepads)
+            .add_systems(
+                Update,
+                controls::save_controls.run_if(resource_exists::<controls::ControlsPath>()),
+            )
+            .add_systems(
+                Update,
+                config::reload_config.run_if(resource_exists::<config::ConfigWatcher>()),
+            )
+            .add_systems(
+                Update,
+                apply_sound_settings.run_if(resource_exists::<Assets<AudioSource>>()),
+            )
+            .add_systems(
+                Update,
+                shields::update_shield_textures.run_if(resource_exists::<Assets<Image>>()),
+            )
+            .add_systems(
+                OnEnter(GameState::Game),
+                (
+                    score::setup,
+                    hud::spawn_hud,
                     player::setup,
                     player::spawn_player,
+                    // Both draw from the gameplay RNG, player two's mystery ship is scheduled
+                    // after player one's. Recorded rounds rely on this order, changing it breaks
+                    // the replay format.
+                    turns::setup.after(mystery_ship::setup),
                     invaders::setup,
                     invaders::spawn_invaders,
+                    rng::reseed.before(mystery_ship::setup),
+                    mystery_ship::setup,
                     shields::spawn_shields,
+                    gamestate::clear_result,
+                    replay::start_playback.run_if(resource_exists::<replay::ReplayPlayback>()),
+                    replay::start_recording.run_if(resource_exists::<replay::ReplayRecording>()),
                 ),
             )
             .add_systems(
-                Update,
+                FixedUpdate,
                 (
                     (
+                        (input::read_keyboard, input::read_gamepad)
+                            .chain()
+                            .run_if(not(resource_exists::<replay::ReplayPlayback>())),
+                        replay::play_back_input.run_if(resource_exists::<replay::ReplayPlayback>()),
+                        replay::record_input.run_if(resource_exists::<replay::ReplayRecording>()),
                         player::move_player,
                         player::shoot,
+                    )
+                        .chain(),
+                    (
+                        invaders::update_invader_speed,
                         invaders::move_invaders,
                         invaders::maybe_shoot,
-                        invaders::check_invader_wall_collision,
-                        invaders::maybe_move_invaders_down,
-                        invaders::check_invaders_reached_bottom,
+                        mystery_ship::spawn_mystery_ship,
+                        mystery_ship::move_mystery_ship,
+                    )
+                        .chain()
+                        .run_if(player::player_alive),
+                    (
                         bullets::spawn_player_bullet,
                         bullets::spawn_invader_bullet,
                         bullets::move_bullets,
+                    )
+                        .chain(),
+                    // Everything has moved, the rules below react to what was hit along the way
+                    (
+                        collision::rebuild_spatial_grid,
+                        collision::detect_collisions,
+                    )
+                        .chain(),
+                    (
+                        invaders::check_invader_wall_collision,
+                        invaders::maybe_move_invaders_down,
+                        shields::erode_shields_under_invaders,
+                        invaders::check_invaders_reached_bottom,
+                    )
+                        .chain()
+                        .run_if(player::player_alive),
+                    // Shields go first, a bullet they stop never reaches what is behind them. Its
+                    // despawn has to be applied before the rules below check which bullets are left.
+                    (
+                        shields::check_bullet_shield_collision,
+                        apply_deferred,
                         bullets::check_bullet_wall_collision,
                         bullets::check_player_bullet_invader_collision,
                         bullets::check_invader_bullet_player_collision,
-                        bullets::player_bullet_sound,
-                        shields::check_bullet_shield_collision,
-                        gamestate::check_player_killed_all_invaders,
-                        gamestate::invader_bullet_hit_player,
+                        mystery_ship::check_player_bullet_mystery_ship_collision,
+                        mystery_ship::count_player_shots,
+                    )
+                        .chain(),
+                    (
+                        player::kill_player,
+                        player::animate_dying_player,
+                        player::respawn_player,
+                        player::tick_invulnerability,
+                        score::award_points,
+                        player::award_extra_lives,
+                    )
+                        .chain(),
+                    // The turns switch before the board is checked, the board cleared in the last
+                    // tick of a turn is picked up again when that player's turn comes back
+                    (
+                        gamestate::player_out_of_lives,
                         gamestate::invaders_hit_player,
-                        gamestate::player_killed_all_invaders,
+                        turns::switch_turns.run_if(resource_exists::<turns::Turns>()),
+                        gamestate::check_player_killed_all_invaders,
+                        waves::start_next_wave,
                     )
                         .chain(),
-                    bevy::window::close_on_esc,
+                )
+                    .chain()
+                    .in_set(SimulationSet::Gameplay),
+            )
+            .add_systems(
+                Update,
+                (
+                    bullets::player_bullet_sound.run_if(resource_exists::<Assets<AudioSource>>()),
+                    score::animate_points_popups,
+                    hud::update_hud,
+                )
+                    .run_if(in_state(GameState::Game)),
+            )
+            .add_systems(
+                OnEnter(GameState::PostGame),
+                (
+                    postgame::spawn_result_screen,
+                    replay::save_recording.run_if(resource_exists::<replay::ReplayRecording>()),
+                    replay::check_playback.run_if(resource_exists::<replay::ReplayPlayback>()),
+                ),
+            )
+            .add_systems(
+                Update,
+                (
+                    postgame::restart.run_if(not(resource_exists::<postgame::NameEntry>())),
+                    postgame::enter_name.run_if(resource_exists::<postgame::NameEntry>()),
+                )
+                    .run_if(in_state(GameState::PostGame)),
+            )
+            .add_systems(
+                OnExit(GameState::PostGame),
+                (
+                    despawn_screen::<postgame::OnPostGameScreen>,
+                    despawn_screen::<OnGameScreen>,
                 ),
             );
     }
 }
 
 #[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
-enum GameState {
+pub enum GameState {
     #[default]
     MainMenu,
     Game,
@@ -72,14 +354,35 @@ fn spawn_camera(mut commands: Commands) {
     commands.spawn(Camera2dBundle::default());
 }
 
-fn setup_sound(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let ambience: Handle<AudioSource> = asset_server.load("space-invaders-drum.wav");
+fn setup_sound(mut commands: Commands, game_assets: Res<assets::GameAssets>) {
+    commands.spawn((
+        AudioBundle {
+            source: game_assets.ambience.clone(),
+            settings: PlaybackSettings::LOOP,
+        },
+        Ambience,
+    ));
+}
 
-    commands.spawn(AudioBundle {
-        source: ambience,
-        settings: PlaybackSettings::LOOP,
-    });
+fn apply_sound_settings(
+    settings: Res<Settings>,
+    sink_query: Query<&AudioSink, Or<(With<Ambience>, With<MysteryShip>)>>,
+) {
+    for sink in sink_query.iter() {
+        if settings.sound && sink.is_paused() {
+            sink.play();
+        } else if !settings.sound && !sink.is_paused() {
+            sink.pause();
+        }
+    }
+}
 
-    let shoot_sound: Handle<AudioSource> = asset_server.load("laser.wav");
-    commands.insert_resource(ShootSound(shoot_sound));
+/// Despawns every entity tagged with the given marker component, along with its children
+pub(crate) fn despawn_screen<T: Component>(
+    to_despawn: Query<Entity, With<T>>,
+    mut commands: Commands,
+) {
+    for entity in &to_despawn {
+        commands.entity(entity).despawn_recursive();
+    }
 }
@@ -1,32 +1,89 @@
-use std::process::exit;
-
 use bevy::prelude::*;
 
 use crate::{
-    Invader, InvaderBulletHitPlayerEvent, InvadersReachedBottomEvent, PlayerKilledAllInvadersEvent,
+    game::GameState, player::Lives, turns::Turns, Invader, InvadersReachedBottomEvent,
+    PlayerKilledAllInvadersEvent, PlayerOutOfLivesEvent, TurnOverEvent,
 };
 
-pub(crate) fn invaders_hit_player(
-    mut invaders_reached_bottom_event: EventReader<InvadersReachedBottomEvent>,
+#[derive(Resource, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GameResult {
+    InvadersReachedBottom,
+    PlayerShot,
+}
+
+impl GameResult {
+    pub(crate) fn title(&self) -> &'static str {
+        match self {
+            GameResult::InvadersReachedBottom | GameResult::PlayerShot => "GAME OVER",
+        }
+    }
+
+    /// Identifier used in machine-readable output
+    pub(crate) fn key(&self) -> &'static str {
+        match self {
+            GameResult::InvadersReachedBottom => "invaders_reached_bottom",
+            GameResult::PlayerShot => "player_shot",
+        }
+    }
+
+    pub(crate) fn description(&self) -> &'static str {
+        match self {
+            GameResult::InvadersReachedBottom => "The invaders reached the bottom",
+            GameResult::PlayerShot => "You were shot down",
+        }
+    }
+}
+
+pub(crate) fn clear_result(mut commands: Commands) {
+    commands.remove_resource::<GameResult>();
+}
+
+fn end_game(
+    commands: &mut Commands,
+    next_state: &mut NextState<GameState>,
+    game_result: GameResult,
 ) {
-    if let Some(_event) = invaders_reached_bottom_event.read().next() {
-        exit(0);
+    // Several end conditions can trigger in the same frame, the first one wins
+    if next_state.0.is_some() {
+        return;
     }
+
+    commands.insert_resource(game_result);
+    next_state.set(GameState::PostGame);
 }
 
-pub(crate) fn invader_bullet_hit_player(
-    mut invader_bullet_hit_player_event: EventReader<InvaderBulletHitPlayerEvent>,
+pub(crate) fn invaders_hit_player(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut invaders_reached_bottom_event: EventReader<InvadersReachedBottomEvent>,
+    turns: Option<Res<Turns>>,
+    mut lives: ResMut<Lives>,
+    mut turn_over_event: EventWriter<TurnOverEvent>,
 ) {
-    if let Some(_event) = invader_bullet_hit_player_event.read().next() {
-        exit(0);
+    if invaders_reached_bottom_event.read().next().is_none() {
+        return;
+    }
+
+    // Only the game of the player in play is over, the other player goes on with their board
+    if turns.is_some_and(|turns| turns.waiting_player_can_play()) {
+        lives.remaining = 0;
+        turn_over_event.send(TurnOverEvent);
+    } else {
+        end_game(
+            &mut commands,
+            &mut next_state,
+            GameResult::InvadersReachedBottom,
+        );
     }
 }
 
-pub(crate) fn player_killed_all_invaders(
-    mut player_killed_all_invaders_event: EventReader<PlayerKilledAllInvadersEvent>,
+pub(crate) fn player_out_of_lives(
+    mut commands: Commands,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut player_out_of_lives_event: EventReader<PlayerOutOfLivesEvent>,
 ) {
-    if let Some(_event) = player_killed_all_invaders_event.read().next() {
-        exit(0);
+    if let Some(_event) = player_out_of_lives_event.read().next() {
+        end_game(&mut commands, &mut next_state, GameResult::PlayerShot);
     }
 }
 
@@ -0,0 +1,110 @@
+use bevy::{
+    app::{AppExit, PluginGroupBuilder},
+    prelude::*,
+};
+
+use crate::{gamestate::GameResult, replay::ReplayPlayback, rng::GameRng, Score, Wave};
+
+/// Everything the game needs to run without a window, audio or rendering
+pub struct HeadlessPlugins;
+
+impl PluginGroup for HeadlessPlugins {
+    fn build(self) -> PluginGroupBuilder {
+        MinimalPlugins
+            .build()
+            .add(bevy::input::InputPlugin)
+            .add(WindowPlugin {
+                primary_window: None,
+                exit_condition: bevy::window::ExitCondition::DontExit,
+                close_when_requested: false,
+            })
+    }
+}
+
+/// Present when the game runs without a window, it ends at game over or after a fixed number of
+/// simulation ticks and prints a one line summary to stdout
+#[derive(Resource)]
+pub struct HeadlessRun {
+    max_ticks: Option<u32>,
+    ticks: u32,
+}
+
+impl HeadlessRun {
+    pub fn new(max_ticks: Option<u32>) -> Self {
+        HeadlessRun {
+            max_ticks,
+            ticks: 0,
+        }
+    }
+
+    fn reached_tick_limit(&self) -> bool {
+        self.max_ticks
+            .is_some_and(|max_ticks| self.ticks >= max_ticks)
+    }
+}
+
+pub(crate) fn count_tick(mut headless_run: ResMut<HeadlessRun>) {
+    headless_run.ticks += 1;
+}
+
+fn print_summary(
+    headless_run: &HeadlessRun,
+    result: &str,
+    score: &Score,
+    wave: &Wave,
+    game_rng: &GameRng,
+    replay: Option<&str>,
+) {
+    let mut summary = format!(
+        "result={} score={} wave={} ticks={} seed={}",
+        result, score.0, wave.0, headless_run.ticks, game_rng.seed
+    );
+    if let Some(replay) = replay {
+        summary.push_str(&format!(" replay={}", replay));
+    }
+
+    println!("{}", summary);
+}
+
+pub(crate) fn stop_at_tick_limit(
+    headless_run: Res<HeadlessRun>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+    game_rng: Res<GameRng>,
+    mut app_exit_event: EventWriter<AppExit>,
+) {
+    if !headless_run.reached_tick_limit() {
+        return;
+    }
+
+    print_summary(&headless_run, "tick_limit", &score, &wave, &game_rng, None);
+    app_exit_event.send(AppExit);
+}
+
+pub(crate) fn stop_at_game_over(
+    headless_run: Res<HeadlessRun>,
+    game_result: Res<GameResult>,
+    score: Res<Score>,
+    wave: Res<Wave>,
+    game_rng: Res<GameRng>,
+    playback: Option<Res<ReplayPlayback>>,
+    mut app_exit_event: EventWriter<AppExit>,
+) {
+    let replay = playback.map(|playback| {
+        if playback.matches(&score) {
+            "match"
+        } else {
+            "desync"
+        }
+    });
+
+    print_summary(
+        &headless_run,
+        game_result.key(),
+        &score,
+        &wave,
+        &game_rng,
+        replay,
+    );
+    app_exit_event.send(AppExit);
+}
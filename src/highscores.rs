@@ -0,0 +1,127 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    data_files::{self, VersionedFile},
+    HiScore,
+};
+
+const HIGH_SCORES_FILE_NAME: &str = "highscores.ron";
+pub(crate) const MAX_HIGH_SCORES: usize = 10;
+pub(crate) const MAX_NAME_LENGTH: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub(crate) struct HighScoreEntry {
+    pub(crate) name: String,
+    pub(crate) score: u32,
+}
+
+/// The best scores ever reached, sorted from highest to lowest
+#[derive(Resource, Default, Debug)]
+pub(crate) struct HighScores {
+    pub(crate) entries: Vec<HighScoreEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct HighScoresFile {
+    version: u32,
+    entries: Vec<HighScoreEntry>,
+}
+
+impl VersionedFile for HighScoresFile {
+    const VERSION: u32 = 1;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl HighScores {
+    pub(crate) fn qualifies(&self, score: u32) -> bool {
+        score > 0
+            && (self.entries.len() < MAX_HIGH_SCORES
+                || self.entries.iter().any(|entry| score > entry.score))
+    }
+
+    pub(crate) fn insert(&mut self, entry: HighScoreEntry) {
+        // Ties keep the older entry first
+        let position = self
+            .entries
+            .iter()
+            .position(|existing| entry.score > existing.score)
+            .unwrap_or(self.entries.len());
+        self.entries.insert(position, entry);
+        self.entries.truncate(MAX_HIGH_SCORES);
+    }
+
+    pub(crate) fn best(&self) -> u32 {
+        self.entries.first().map_or(0, |entry| entry.score)
+    }
+
+    fn from_ron(contents: &str) -> Result<Self, String> {
+        let file: HighScoresFile = data_files::from_ron(contents)?;
+        let mut high_scores = HighScores::default();
+        for entry in file.entries {
+            high_scores.insert(entry);
+        }
+
+        Ok(high_scores)
+    }
+
+    fn to_ron(&self) -> Result<String, ron::Error> {
+        data_files::to_ron(&HighScoresFile {
+            version: HighScoresFile::VERSION,
+            entries: self.entries.clone(),
+        })
+    }
+}
+
+pub(crate) fn load_high_scores(mut commands: Commands, mut hi_score: ResMut<HiScore>) {
+    let high_scores = match data_files::data_path(HIGH_SCORES_FILE_NAME) {
+        None => {
+            warn!("No data directory available, high scores won't be kept");
+            HighScores::default()
+        }
+        Some(path) => match data_files::read(&path) {
+            Ok(None) => HighScores::default(),
+            Ok(Some(contents)) => HighScores::from_ron(&contents).unwrap_or_else(|error| {
+                warn!(
+                    "Ignoring corrupt high scores file {}: {}",
+                    path.display(),
+                    error
+                );
+                HighScores::default()
+            }),
+            Err(error) => {
+                warn!(
+                    "Could not read high scores file {}: {}",
+                    path.display(),
+                    error
+                );
+                HighScores::default()
+            }
+        },
+    };
+
+    hi_score.0 = hi_score.0.max(high_scores.best());
+    commands.insert_resource(high_scores);
+}
+
+pub(crate) fn save_high_scores(high_scores: &HighScores) {
+    let Some(path) = data_files::data_path(HIGH_SCORES_FILE_NAME) else {
+        return;
+    };
+
+    let result = high_scores
+        .to_ron()
+        .map_err(|error| error.to_string())
+        .and_then(|contents| data_files::write(&path, &contents));
+
+    if let Err(error) = result {
+        error!(
+            "Could not save high scores to {}: {}",
+            path.display(),
+            error
+        );
+    }
+}
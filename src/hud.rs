@@ -0,0 +1,103 @@
+use bevy::prelude::*;
+
+use crate::{
+    player::Lives,
+    turns::{PlayersConfig, Turns},
+    HiScore, OnGameScreen, Score, Wave,
+};
+
+const HUD_FONT_SIZE: f32 = 25.0;
+const HUD_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const HUD_PADDING: f32 = 15.0;
+
+/// Which value a HUD text displays
+#[derive(Component)]
+pub(crate) enum HudText {
+    Score,
+    /// Score of one of the players in a two-player round, starting at 0 for player one
+    PlayerScore(usize),
+    /// Whose turn it is in a two-player round
+    Turn,
+    HiScore,
+    Wave,
+    Lives,
+}
+
+pub(crate) fn spawn_hud(mut commands: Commands, players_config: Res<PlayersConfig>) {
+    let hud_texts = if players_config.players < 2 {
+        vec![
+            HudText::Score,
+            HudText::HiScore,
+            HudText::Wave,
+            HudText::Lives,
+        ]
+    } else {
+        vec![
+            HudText::PlayerScore(0),
+            HudText::HiScore,
+            HudText::PlayerScore(1),
+            HudText::Turn,
+            HudText::Wave,
+            HudText::Lives,
+        ]
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    position_type: PositionType::Absolute,
+                    top: Val::Px(0.0),
+                    justify_content: JustifyContent::SpaceBetween,
+                    padding: UiRect::all(Val::Px(HUD_PADDING)),
+                    ..default()
+                },
+                ..default()
+            },
+            OnGameScreen,
+        ))
+        .with_children(|parent| {
+            for hud_text in hud_texts {
+                parent.spawn((
+                    TextBundle::from_section(
+                        "",
+                        TextStyle {
+                            font_size: HUD_FONT_SIZE,
+                            color: HUD_COLOR,
+                            ..default()
+                        },
+                    ),
+                    hud_text,
+                ));
+            }
+        });
+}
+
+pub(crate) fn update_hud(
+    score: Res<Score>,
+    hi_score: Res<HiScore>,
+    wave: Res<Wave>,
+    lives: Res<Lives>,
+    turns: Option<Res<Turns>>,
+    mut text_query: Query<(&mut Text, &HudText)>,
+) {
+    for (mut text, hud_text) in text_query.iter_mut() {
+        text.sections[0].value = match hud_text {
+            HudText::Score => format!("SCORE {:05}", score.0),
+            HudText::PlayerScore(player) => {
+                let player_score = turns
+                    .as_ref()
+                    .map_or(score.0, |turns| turns.scores(&score)[*player]);
+                format!("{}UP {:05}", player + 1, player_score)
+            }
+            HudText::Turn => format!(
+                "PLAYER {}",
+                turns.as_ref().map_or(0, |turns| turns.current_player()) + 1
+            ),
+            HudText::HiScore => format!("HI-SCORE {:05}", hi_score.0),
+            HudText::Wave => format!("WAVE {}", wave.0),
+            HudText::Lives => format!("LIVES {}", lives.remaining),
+        };
+    }
+}
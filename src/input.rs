@@ -0,0 +1,171 @@
+use bevy::{
+    input::gamepad::{GamepadConnection, GamepadConnectionEvent},
+    prelude::*,
+};
+
+use crate::{
+    controls::{Action, InputMap},
+    turns::Turns,
+};
+
+/// Sticks pushed less than this far from the center don't move the player
+const STICK_DEADZONE: f32 = 0.35;
+const FIRE_BUTTONS: [GamepadButtonType; 4] = [
+    GamepadButtonType::South,
+    GamepadButtonType::East,
+    GamepadButtonType::West,
+    GamepadButtonType::North,
+];
+/// Number of players a gamepad can be assigned to
+const GAMEPAD_SLOTS: usize = 2;
+
+/// What the player asks for during the current simulation tick.
+///
+/// Gameplay systems only look at this, never at the devices, so a tick plays out the same
+/// whether its input came from the keyboard or from a replay.
+#[derive(Resource, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) struct PlayerInput {
+    pub(crate) left: bool,
+    pub(crate) right: bool,
+    pub(crate) fire: bool,
+}
+
+impl PlayerInput {
+    const LEFT: u8 = 1;
+    const RIGHT: u8 = 1 << 1;
+    const FIRE: u8 = 1 << 2;
+
+    pub(crate) fn to_bits(self) -> u8 {
+        let mut bits = 0;
+        if self.left {
+            bits |= Self::LEFT;
+        }
+        if self.right {
+            bits |= Self::RIGHT;
+        }
+        if self.fire {
+            bits |= Self::FIRE;
+        }
+        bits
+    }
+
+    pub(crate) fn from_bits(bits: u8) -> Self {
+        PlayerInput {
+            left: bits & Self::LEFT != 0,
+            right: bits & Self::RIGHT != 0,
+            fire: bits & Self::FIRE != 0,
+        }
+    }
+}
+
+pub(crate) fn read_keyboard(
+    keyboard_input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    *player_input = PlayerInput {
+        left: input_map.pressed(Action::MoveLeft, &keyboard_input),
+        right: input_map.pressed(Action::MoveRight, &keyboard_input),
+        fire: input_map.pressed(Action::Fire, &keyboard_input),
+    };
+}
+
+/// The gamepad of each player, in the order they were plugged in. The slot of a pad that is
+/// unplugged goes to a pad left waiting, or else to the next one plugged in.
+#[derive(Resource, Default, Debug)]
+pub struct GamepadSlots {
+    slots: [Option<Gamepad>; GAMEPAD_SLOTS],
+}
+
+impl GamepadSlots {
+    /// The gamepad of the player, starting at 0 for player one
+    pub fn gamepad(&self, player: usize) -> Option<Gamepad> {
+        self.slots.get(player).copied().flatten()
+    }
+
+    fn connect(&mut self, gamepad: Gamepad) -> Option<usize> {
+        if let Some(player) = self.slots.iter().position(|slot| *slot == Some(gamepad)) {
+            return Some(player);
+        }
+        let player = self.slots.iter().position(Option::is_none)?;
+        self.slots[player] = Some(gamepad);
+        Some(player)
+    }
+
+    fn disconnect(&mut self, gamepad: Gamepad) -> Option<usize> {
+        let player = self.slots.iter().position(|slot| *slot == Some(gamepad))?;
+        self.slots[player] = None;
+        Some(player)
+    }
+
+    /// A connected pad that has no player yet, the lowest id first so the pick doesn't depend on
+    /// the order of the connected pads
+    fn waiting(&self, gamepads: &Gamepads) -> Option<Gamepad> {
+        gamepads
+            .iter()
+            .filter(|gamepad| !self.slots.contains(&Some(*gamepad)))
+            .min_by_key(|gamepad| gamepad.id)
+    }
+}
+
+pub(crate) fn assign_gamepads(
+    mut connection_events: EventReader<GamepadConnectionEvent>,
+    mut gamepad_slots: ResMut<GamepadSlots>,
+    gamepads: Res<Gamepads>,
+) {
+    for event in connection_events.read() {
+        match &event.connection {
+            GamepadConnection::Connected(info) => match gamepad_slots.connect(event.gamepad) {
+                Some(player) => info!("Gamepad {} plays for player {}", info.name, player + 1),
+                None => info!(
+                    "Gamepad {} connected, every player already has one",
+                    info.name
+                ),
+            },
+            GamepadConnection::Disconnected => {
+                let Some(player) = gamepad_slots.disconnect(event.gamepad) else {
+                    continue;
+                };
+                info!("Gamepad of player {} disconnected", player + 1);
+
+                if let Some(waiting) = gamepad_slots.waiting(&gamepads) {
+                    gamepad_slots.connect(waiting);
+                    info!(
+                        "Gamepad {} now plays for player {}",
+                        gamepads.name(waiting).unwrap_or("?"),
+                        player + 1
+                    );
+                }
+            }
+        }
+    }
+}
+
+/// Adds the gamepad of the player whose turn it is to what the keyboard asked for
+pub(crate) fn read_gamepad(
+    gamepad_slots: Res<GamepadSlots>,
+    turns: Option<Res<Turns>>,
+    buttons: Res<Input<GamepadButton>>,
+    axes: Res<Axis<GamepadAxis>>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    let player = turns.map_or(0, |turns| turns.current_player());
+    let Some(gamepad) = gamepad_slots.gamepad(player) else {
+        return;
+    };
+    let pressed = |button_type| buttons.pressed(GamepadButton::new(gamepad, button_type));
+    let stick_x = axes
+        .get(GamepadAxis::new(gamepad, GamepadAxisType::LeftStickX))
+        .unwrap_or(0.0);
+
+    player_input.left |= pressed(GamepadButtonType::DPadLeft) || stick_x < -STICK_DEADZONE;
+    player_input.right |= pressed(GamepadButtonType::DPadRight) || stick_x > STICK_DEADZONE;
+    player_input.fire |= FIRE_BUTTONS.into_iter().any(pressed);
+}
+
+/// True on the update Start is pressed on any gamepad
+pub(crate) fn start_just_pressed(buttons: &Input<GamepadButton>) -> bool {
+    buttons
+        .get_just_pressed()
+        .any(|button| button.button_type == GamepadButtonType::Start)
+}
@@ -0,0 +1,45 @@
+use bevy::prelude::*;
+
+/// Smooths the movement of an entity between fixed simulation ticks.
+///
+/// Gameplay systems move the `Transform` during `FixedUpdate`; the simulated positions of the
+/// last two ticks are kept here so the rendered position can be blended between them.
+#[derive(Component)]
+pub(crate) struct InterpolatedPosition {
+    previous: Vec3,
+    current: Vec3,
+}
+
+impl InterpolatedPosition {
+    pub(crate) fn new(position: Vec3) -> Self {
+        InterpolatedPosition {
+            previous: position,
+            current: position,
+        }
+    }
+}
+
+/// Puts the simulated position back before the tick runs, undoing the blending
+pub(crate) fn restore_positions(mut query: Query<(&mut Transform, &mut InterpolatedPosition)>) {
+    for (mut transform, mut position) in query.iter_mut() {
+        transform.translation = position.current;
+        position.previous = position.current;
+    }
+}
+
+pub(crate) fn save_positions(mut query: Query<(&Transform, &mut InterpolatedPosition)>) {
+    for (transform, mut position) in query.iter_mut() {
+        position.current = transform.translation;
+    }
+}
+
+pub(crate) fn interpolate_positions(
+    mut query: Query<(&mut Transform, &InterpolatedPosition)>,
+    fixed_time: Res<Time<Fixed>>,
+) {
+    let blend = fixed_time.overstep_percentage();
+
+    for (mut transform, position) in query.iter_mut() {
+        transform.translation = position.previous.lerp(position.current, blend);
+    }
+}
@@ -1,52 +1,49 @@
-use std::time::Duration;
+use std::{collections::BTreeMap, time::Duration};
 
 use bevy::prelude::*;
-use rand::prelude::*;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::{
-    walls, Collider, Invader, InvaderBulletFiredEvent, InvaderDifficulty,
-    InvadersReachedBottomEvent, INVADER_SIZE, PLAYER_Y,
+    assets::GameAssets,
+    config::{GameConfig, InvaderTuning},
+    levels::{Level, LevelSequence},
+    rng::GameRng,
+    walls, CollisionEvent, CollisionLayer, Hitbox, Invader, InvaderBullet, InvaderBulletFiredEvent,
+    InvaderDifficulty, InvadersReachedBottomEvent, OnGameScreen, Player, Wave, INVADER_SIZE,
+    PLAYER_Y,
 };
 
-const MOVEMENT_RATE: f32 = 0.1;
-const MOVEMENT: f32 = 5.0;
-const MOVEMENT_TIMER_INCREASE_FACTOR: f32 = 0.8;
-const INVADER_WALL_PADDING: f32 = 20.0;
-const MOVE_DOWN_AMOUNT: f32 = 15.0;
 const PLAYER_COLLISION_Y: f32 = PLAYER_Y + 20.0;
+/// Distance between invaders, the classic 5 by 11 formation spans 600 by 300
+const HORIZONTAL_SPACING: f32 = 600.0 / 11.0;
+const VERTICAL_SPACING: f32 = 60.0;
+
+/// Column order used by the rolling shot, taken from the arcade plunger shot table
+const ROLLING_SHOT_COLUMNS: [i32; 16] = [0, 6, 0, 0, 0, 3, 10, 0, 5, 2, 0, 0, 10, 8, 1, 7];
 
 impl InvaderDifficulty {
-    fn from_i32(value: i32) -> Self {
-        match value {
-            0 => InvaderDifficulty::Easy,
-            1 => InvaderDifficulty::Medium,
-            2 => InvaderDifficulty::Hard,
-            3 => InvaderDifficulty::Medium,
-            _ => InvaderDifficulty::Easy,
+    fn get_sprite(&self, game_assets: &GameAssets) -> Handle<Image> {
+        match self {
+            InvaderDifficulty::Easy => game_assets.green_invader.clone(),
+            InvaderDifficulty::Medium => game_assets.yellow_invader.clone(),
+            InvaderDifficulty::Hard => game_assets.red_invader.clone(),
         }
     }
 
-    fn get_sprite_path(&self) -> String {
-        String::from(match self {
-            InvaderDifficulty::Easy => "green.png",
-            InvaderDifficulty::Medium => "yellow.png",
-            InvaderDifficulty::Hard => "red.png",
-        })
-    }
-
-    pub(crate) fn get_bullet_sprite_path(&self) -> String {
-        String::from(match self {
-            InvaderDifficulty::Easy => "green-bullet.png",
-            InvaderDifficulty::Medium => "yellow-bullet.png",
-            InvaderDifficulty::Hard => "red-bullet.png",
-        })
+    pub(crate) fn get_bullet_sprite(&self, game_assets: &GameAssets) -> Handle<Image> {
+        match self {
+            InvaderDifficulty::Easy => game_assets.green_bullet.clone(),
+            InvaderDifficulty::Medium => game_assets.yellow_bullet.clone(),
+            InvaderDifficulty::Hard => game_assets.red_bullet.clone(),
+        }
     }
 
-    pub(crate) fn get_bullet_speed(&self) -> f32 {
+    pub(crate) fn get_points(&self) -> u32 {
         match self {
-            InvaderDifficulty::Easy => 200.0,
-            InvaderDifficulty::Medium => 350.0,
-            InvaderDifficulty::Hard => 500.0,
+            InvaderDifficulty::Easy => 10,
+            InvaderDifficulty::Medium => 20,
+            InvaderDifficulty::Hard => 30,
         }
     }
 }
@@ -57,58 +54,181 @@ pub(crate) struct InvaderConfig {
     direction: f32,
     wall_collision_timer: Timer,
     move_down: bool,
+    /// Level played in this wave, its overrides take precedence over the game config
+    level: Level,
+    wave: u32,
+    /// Accumulated speedup from every descent since the wave started
+    descent_speedup: f32,
+    formation_size: usize,
 }
 
-pub(crate) fn setup(mut commands: Commands) {
-    commands.insert_resource(InvaderConfig {
-        movement_timer: Timer::new(Duration::from_secs_f32(MOVEMENT_RATE), TimerMode::Repeating),
-        wall_collision_timer: Timer::new(Duration::from_secs_f32(1.0), TimerMode::Once),
-        direction: 1.0,
-        move_down: false,
-    });
+impl InvaderConfig {
+    pub(crate) fn new(wave: u32, levels: &LevelSequence, tuning: &InvaderTuning) -> Self {
+        let level = levels.for_wave(wave);
+        let mut invader_config = InvaderConfig {
+            movement_timer: Timer::default(),
+            wall_collision_timer: Timer::new(Duration::from_secs_f32(1.0), TimerMode::Once),
+            direction: 1.0,
+            move_down: false,
+            level: level.clone(),
+            wave,
+            descent_speedup: 1.0,
+            formation_size: level.invaders().count(),
+        };
+        invader_config.movement_timer = Timer::new(
+            Duration::from_secs_f32(wave_movement_rate(wave, levels, tuning)),
+            TimerMode::Repeating,
+        );
+        invader_config
+    }
 }
 
-pub(crate) fn spawn_invaders(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let n_rows = 5;
-    let n_cols = 11;
+/// Time between steps of a full formation in the given wave. The speedup applies once per wave
+/// on top of the fastest level played so far, so a slower level never makes a wave slower than
+/// the one before it.
+pub fn wave_movement_rate(wave: u32, levels: &LevelSequence, tuning: &InvaderTuning) -> f32 {
+    let fastest_level_rate = (1..=wave.min(levels.levels.len() as u32))
+        .map(|played| {
+            levels
+                .for_wave(played)
+                .movement_rate
+                .unwrap_or(tuning.movement_rate)
+        })
+        .fold(f32::INFINITY, f32::min);
+
+    (fastest_level_rate * tuning.wave_speedup.powi(wave as i32 - 1)).max(tuning.min_movement_rate)
+}
 
-    let height = 300.0;
-    let max_height = walls::TOP_WALL - 50.0;
-    let width = (-walls::LEFT_WALL + walls::RIGHT_WALL) / 1.5;
-    let horizontal_spacing = width / n_cols as f32;
-    let vertical_spacing = height / n_rows as f32;
-    let starting_x = -(width / 2.0);
-    let starting_position = Vec3::new(starting_x, max_height, 0.0);
+/// How a column is picked when the formation fires
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ShotPattern {
+    RandomColumn,
+    ColumnAbovePlayer,
+    RollingSequence,
+}
 
-    let mut invader_position = starting_position;
+#[derive(Resource)]
+pub(crate) struct InvaderShootState {
+    timer: Timer,
+    next_pattern: usize,
+    next_rolling_column: usize,
+}
 
-    (0..n_rows).for_each(|row| {
-        (0..n_cols).for_each(|_column| {
-            let invader: Invader = Invader {
-                difficulty: InvaderDifficulty::from_i32(row),
+impl InvaderShootState {
+    pub(crate) fn new(config: &GameConfig) -> Self {
+        InvaderShootState {
+            timer: Timer::from_seconds(config.invader_fire.shot_interval, TimerMode::Repeating),
+            next_pattern: 0,
+            next_rolling_column: 0,
+        }
+    }
+}
+
+pub(crate) fn setup(mut commands: Commands, levels: Res<LevelSequence>, config: Res<GameConfig>) {
+    commands.insert_resource(Wave(1));
+    commands.insert_resource(InvaderConfig::new(1, &levels, &config.invaders));
+    commands.insert_resource(InvaderShootState::new(&config));
+}
+
+pub(crate) fn spawn_invaders(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    levels: Res<LevelSequence>,
+    config: Res<GameConfig>,
+) {
+    spawn_formation(&mut commands, &game_assets, &levels, &config.invaders, 1);
+}
+
+/// Spawns the formation of the wave's level
+pub(crate) fn spawn_formation(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    levels: &LevelSequence,
+    tuning: &InvaderTuning,
+    wave: u32,
+) {
+    let level = levels.for_wave(wave);
+    info!("Wave {}: {}", wave, level.name);
+
+    for (invader, position) in formation(levels, tuning, wave) {
+        spawn_invader(commands, game_assets, invader, position);
+    }
+}
+
+/// Invaders of the wave's level where they start, later waves start closer to the player
+pub(crate) fn formation(
+    levels: &LevelSequence,
+    tuning: &InvaderTuning,
+    wave: u32,
+) -> Vec<(Invader, Vec3)> {
+    let level = levels.for_wave(wave);
+    let start_drop = (tuning.wave_start_drop * (wave - 1) as f32).min(tuning.max_wave_start_drop);
+    let max_height = walls::TOP_WALL - 50.0 - start_drop;
+    let starting_x = -(level.columns() as f32 * HORIZONTAL_SPACING) / 2.0;
+
+    level
+        .invaders()
+        .map(|(row, column, difficulty)| {
+            let invader_position = Vec3::new(
+                starting_x + column as f32 * HORIZONTAL_SPACING,
+                max_height - row as f32 * VERTICAL_SPACING,
+                0.0,
+            );
+            let invader = Invader {
+                difficulty,
+                column: column as i32,
             };
-            let invader_sprite_path = invader.difficulty.get_sprite_path();
-            commands.spawn((
-                invader,
-                SpriteBundle {
-                    texture: asset_server.load(invader_sprite_path),
-                    transform: Transform {
-                        translation: invader_position,
-                        ..default()
-                    },
-                    ..default()
-                },
-            ));
-            invader_position.x += horizontal_spacing;
-        });
-        invader_position.x = starting_x;
-        invader_position.y -= vertical_spacing;
-    });
+            (invader, invader_position)
+        })
+        .collect()
+}
+
+pub(crate) fn spawn_invader(
+    commands: &mut Commands,
+    game_assets: &GameAssets,
+    invader: Invader,
+    position: Vec3,
+) {
+    let invader_sprite = invader.difficulty.get_sprite(game_assets);
+    commands.spawn((
+        invader,
+        Hitbox::aabb(CollisionLayer::Invader, INVADER_SIZE),
+        SpriteBundle {
+            texture: invader_sprite,
+            transform: Transform {
+                translation: position,
+                ..default()
+            },
+            ..default()
+        },
+        OnGameScreen,
+    ));
+}
+
+pub(crate) fn update_invader_speed(
+    invader_query: Query<(), With<Invader>>,
+    levels: Res<LevelSequence>,
+    config: Res<GameConfig>,
+    mut invader_config: ResMut<InvaderConfig>,
+) {
+    let remaining = invader_query.iter().count();
+    let curve_factor = config
+        .invaders
+        .speed_factor(remaining, invader_config.formation_size);
+    let movement_rate = (wave_movement_rate(invader_config.wave, &levels, &config.invaders)
+        * invader_config.descent_speedup
+        * curve_factor)
+        .max(config.invaders.min_movement_rate);
+
+    invader_config
+        .movement_timer
+        .set_duration(Duration::from_secs_f32(movement_rate));
 }
 
 pub(crate) fn move_invaders(
     mut invader_query: Query<&mut Transform, With<Invader>>,
     mut invader_config: ResMut<InvaderConfig>,
+    config: Res<GameConfig>,
     time: Res<Time>,
 ) {
     invader_config.movement_timer.tick(time.delta());
@@ -118,61 +238,51 @@ pub(crate) fn move_invaders(
     }
 
     invader_query.iter_mut().for_each(|mut invader_transform| {
-        invader_transform.translation.x += MOVEMENT * invader_config.direction;
+        invader_transform.translation.x += config.invaders.movement * invader_config.direction;
     });
 }
 
 pub(crate) fn check_invader_wall_collision(
-    invader_query: Query<&Transform, With<Invader>>,
-    collider_query: Query<&Transform, With<Collider>>,
+    mut collision_events: EventReader<CollisionEvent>,
     mut invader_config: ResMut<InvaderConfig>,
+    config: Res<GameConfig>,
     time: Res<Time>,
 ) {
+    let hit_wall = collision_events
+        .read()
+        .filter(|event| matches!(event, CollisionEvent::InvaderHitWall { .. }))
+        .count()
+        > 0;
+
     invader_config.wall_collision_timer.tick(time.delta());
 
-    if !invader_config.wall_collision_timer.finished() {
+    if !hit_wall || !invader_config.wall_collision_timer.finished() {
         return;
     }
 
-    for invader_transform in invader_query.iter() {
-        for collider_transform in collider_query.iter() {
-            if bevy::sprite::collide_aabb::collide(
-                invader_transform.translation,
-                Vec2::splat(INVADER_SIZE + INVADER_WALL_PADDING),
-                collider_transform.translation,
-                collider_transform.scale.truncate() + walls::WALL_THICKNESS,
-            )
-            .is_some()
-            {
-                invader_config.direction *= -1.0;
-                invader_config.wall_collision_timer.reset();
-                invader_config.move_down = true;
-
-                let current_movement_timer = invader_config.movement_timer.duration().as_secs_f32();
-
-                invader_config
-                    .movement_timer
-                    .set_duration(Duration::from_secs_f32(
-                        current_movement_timer * MOVEMENT_TIMER_INCREASE_FACTOR,
-                    ));
-                return;
-            }
-        }
-    }
+    invader_config.direction *= -1.0;
+    invader_config.wall_collision_timer.reset();
+    invader_config.move_down = true;
+    invader_config.descent_speedup *= config.invaders.descent_speedup;
 }
 
 pub(crate) fn maybe_move_invaders_down(
     mut invader_query: Query<&mut Transform, With<Invader>>,
     mut invader_config: ResMut<InvaderConfig>,
+    config: Res<GameConfig>,
 ) {
     if !invader_config.move_down {
         return;
     }
 
     invader_config.move_down = false;
+    let move_down_amount = invader_config
+        .level
+        .move_down_amount
+        .unwrap_or(config.invaders.move_down_amount);
 
     invader_query.iter_mut().for_each(|mut invader_transform| {
-        invader_transform.translation.y -= MOVE_DOWN_AMOUNT;
+        invader_transform.translation.y -= move_down_amount;
     });
 }
 
@@ -189,18 +299,90 @@ pub(crate) fn check_invaders_reached_bottom(
 }
 
 pub(crate) fn maybe_shoot(
-    invader_query: Query<(&Invader, &Transform), With<Invader>>,
+    invader_query: Query<(&Invader, &Transform)>,
+    invader_bullet_query: Query<(), With<InvaderBullet>>,
+    player_query: Query<&Transform, With<Player>>,
+    config: Res<GameConfig>,
+    invader_config: Res<InvaderConfig>,
+    mut shoot_state: ResMut<InvaderShootState>,
+    mut game_rng: ResMut<GameRng>,
+    time: Res<Time>,
     mut invader_bullet_fired_event: EventWriter<InvaderBulletFiredEvent>,
 ) {
+    let fire_config = &config.invader_fire;
+    let shot_interval = invader_config
+        .level
+        .shot_interval
+        .unwrap_or(fire_config.shot_interval);
+    shoot_state
+        .timer
+        .set_duration(Duration::from_secs_f32(shot_interval));
+    if !shoot_state.timer.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    if invader_bullet_query.iter().count() >= fire_config.max_bullets
+        || fire_config.patterns.is_empty()
+    {
+        return;
+    }
+
+    // Only the lowest invader of each column can fire, the others would hit their own formation
+    let mut shooters: BTreeMap<i32, (&Invader, Vec3)> = BTreeMap::new();
     for (invader, invader_transform) in invader_query.iter() {
-        let rng = rand::thread_rng().gen::<f32>();
-        if rng < 0.999 {
-            continue;
-        }
+        let position = invader_transform.translation;
+        shooters
+            .entry(invader.column)
+            .and_modify(|shooter| {
+                if position.y < shooter.1.y {
+                    *shooter = (invader, position);
+                }
+            })
+            .or_insert((invader, position));
+    }
 
-        invader_bullet_fired_event.send(InvaderBulletFiredEvent {
-            position: invader_transform.translation + Vec3::new(0.0, -10.0, 0.0),
-            invader_difficulty: invader.difficulty.clone(),
-        });
+    if shooters.is_empty() {
+        return;
     }
+
+    let pattern = fire_config.patterns[shoot_state.next_pattern % fire_config.patterns.len()];
+    shoot_state.next_pattern = (shoot_state.next_pattern + 1) % fire_config.patterns.len();
+
+    let column = match pattern {
+        ShotPattern::RandomColumn => {
+            let index = game_rng.gameplay.gen_range(0..shooters.len());
+            shooters.keys().nth(index).copied()
+        }
+        ShotPattern::ColumnAbovePlayer => {
+            let player_x = player_query
+                .get_single()
+                .map_or(0.0, |player_transform| player_transform.translation.x);
+            shooters
+                .iter()
+                .min_by(|(_, (_, a)), (_, (_, b))| {
+                    (a.x - player_x).abs().total_cmp(&(b.x - player_x).abs())
+                })
+                .map(|(column, _)| *column)
+        }
+        ShotPattern::RollingSequence => {
+            // The table doesn't name every column, the nearest live one fires in place of a
+            // column that is gone or missing from the table
+            let target = ROLLING_SHOT_COLUMNS[shoot_state.next_rolling_column];
+            shoot_state.next_rolling_column =
+                (shoot_state.next_rolling_column + 1) % ROLLING_SHOT_COLUMNS.len();
+            shooters
+                .keys()
+                .min_by_key(|column| (*column - target).abs())
+                .copied()
+        }
+    };
+
+    let Some((invader, position)) = column.and_then(|column| shooters.get(&column)) else {
+        return;
+    };
+
+    invader_bullet_fired_event.send(InvaderBulletFiredEvent {
+        position: *position + Vec3::new(0.0, -10.0, 0.0),
+        invader_difficulty: invader.difficulty.clone(),
+    });
 }
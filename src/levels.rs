@@ -0,0 +1,226 @@
+use std::{
+    collections::BTreeMap,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::{asset::io::file::FileAssetReader, prelude::*};
+use serde::{Deserialize, Serialize};
+
+use crate::{walls, InvaderDifficulty};
+
+const LEVELS_DIRECTORY: &str = "levels";
+const SEQUENCE_FILE_NAME: &str = "sequence.ron";
+/// Larger formations would start inside the walls or too close to the player
+const MAX_FORMATION_ROWS: usize = 6;
+const MAX_FORMATION_COLUMNS: usize = 13;
+const EMPTY_CELLS: [char; 2] = ['.', ' '];
+const MAX_SHIELD_X: f32 = walls::RIGHT_WALL - 50.0;
+
+/// One wave of invaders, read from a file in `assets/levels`
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct Level {
+    pub name: String,
+    /// Rows of the formation from top to bottom, one character per invader, `.` or a space
+    /// leaves the cell empty
+    pub formation: Vec<String>,
+    /// Invader type of each character used in the formation
+    #[serde(default = "default_legend")]
+    pub legend: BTreeMap<char, InvaderDifficulty>,
+    /// Overrides the time between steps of the full formation from the game config
+    #[serde(default)]
+    pub movement_rate: Option<f32>,
+    /// Overrides how far the formation drops at each wall from the game config
+    #[serde(default)]
+    pub move_down_amount: Option<f32>,
+    /// Overrides the time between invader shots from the game config
+    #[serde(default)]
+    pub shot_interval: Option<f32>,
+    /// Horizontal position of every shield, the default row is used when missing
+    #[serde(default)]
+    pub shields: Option<Vec<f32>>,
+}
+
+fn default_legend() -> BTreeMap<char, InvaderDifficulty> {
+    BTreeMap::from([
+        ('E', InvaderDifficulty::Easy),
+        ('M', InvaderDifficulty::Medium),
+        ('H', InvaderDifficulty::Hard),
+    ])
+}
+
+impl Default for Level {
+    /// The arcade formation, the only level when no level files are found
+    fn default() -> Self {
+        Level {
+            name: "Classic".to_string(),
+            formation: vec![
+                "EEEEEEEEEEE".to_string(),
+                "MMMMMMMMMMM".to_string(),
+                "HHHHHHHHHHH".to_string(),
+                "MMMMMMMMMMM".to_string(),
+                "EEEEEEEEEEE".to_string(),
+            ],
+            legend: default_legend(),
+            movement_rate: None,
+            move_down_amount: None,
+            shot_interval: None,
+            shields: None,
+        }
+    }
+}
+
+impl Level {
+    pub fn from_ron(contents: &str) -> Result<Self, String> {
+        // Optional values can be written without `Some(...)`
+        let level: Level = ron::Options::default()
+            .with_default_extension(ron::extensions::Extensions::IMPLICIT_SOME)
+            .from_str(contents)
+            .map_err(|error| error.to_string())?;
+        level.validate()?;
+        Ok(level)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        if self.invaders().next().is_none() {
+            errors.push("formation has no invaders".to_string());
+        }
+        if self.formation.len() > MAX_FORMATION_ROWS {
+            errors.push(format!(
+                "formation has {} rows, at most {} fit",
+                self.formation.len(),
+                MAX_FORMATION_ROWS
+            ));
+        }
+
+        for (row, line) in self.formation.iter().enumerate() {
+            if line.chars().count() > MAX_FORMATION_COLUMNS {
+                errors.push(format!(
+                    "formation row {} has {} columns, at most {} fit",
+                    row + 1,
+                    line.chars().count(),
+                    MAX_FORMATION_COLUMNS
+                ));
+            }
+            for character in line.chars() {
+                if !EMPTY_CELLS.contains(&character) && !self.legend.contains_key(&character) {
+                    errors.push(format!(
+                        "formation row {} uses '{}' which is not in the legend",
+                        row + 1,
+                        character
+                    ));
+                }
+            }
+        }
+
+        let overrides = [
+            ("movement_rate", self.movement_rate),
+            ("move_down_amount", self.move_down_amount),
+            ("shot_interval", self.shot_interval),
+        ];
+        for (name, value) in overrides {
+            if let Some(value) = value {
+                if value.is_nan() || value <= 0.0 {
+                    errors.push(format!("{} must be greater than 0, got {}", name, value));
+                }
+            }
+        }
+
+        for x in self.shields.iter().flatten() {
+            if x.is_nan() || x.abs() > MAX_SHIELD_X {
+                errors.push(format!(
+                    "shield at {} is outside the arena, positions go from -{} to {}",
+                    x, MAX_SHIELD_X, MAX_SHIELD_X
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("\n"))
+        }
+    }
+
+    /// Grid position (row, column) and type of every invader in the formation
+    pub(crate) fn invaders(&self) -> impl Iterator<Item = (usize, usize, InvaderDifficulty)> + '_ {
+        self.formation
+            .iter()
+            .enumerate()
+            .flat_map(move |(row, line)| {
+                line.chars()
+                    .enumerate()
+                    .filter_map(move |(column, character)| {
+                        self.legend
+                            .get(&character)
+                            .map(|difficulty| (row, column, difficulty.clone()))
+                    })
+            })
+    }
+
+    pub(crate) fn columns(&self) -> usize {
+        self.formation
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or(0)
+    }
+}
+
+/// The levels played in order, the sequence starts over once the last one is cleared
+#[derive(Resource, Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct LevelSequence {
+    pub levels: Vec<Level>,
+}
+
+impl Default for LevelSequence {
+    fn default() -> Self {
+        LevelSequence {
+            levels: vec![Level::default()],
+        }
+    }
+}
+
+impl LevelSequence {
+    /// Reads `sequence.ron`, a list of level file names, and every level it names. `None` means
+    /// there is no sequence file and the default level applies.
+    pub fn load(directory: &Path) -> Result<Option<Self>, String> {
+        let contents = match fs::read_to_string(directory.join(SEQUENCE_FILE_NAME)) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(error) => return Err(format!("{}: {}", SEQUENCE_FILE_NAME, error)),
+        };
+
+        let file_names: Vec<String> = ron::from_str(&contents)
+            .map_err(|error| format!("{}: {}", SEQUENCE_FILE_NAME, error))?;
+        if file_names.is_empty() {
+            return Err(format!("{}: the sequence is empty", SEQUENCE_FILE_NAME));
+        }
+
+        let levels = file_names
+            .iter()
+            .map(|file_name| {
+                fs::read_to_string(directory.join(file_name))
+                    .map_err(|error| error.to_string())
+                    .and_then(|contents| Level::from_ron(&contents))
+                    .map_err(|error| format!("{}: {}", file_name, error))
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Some(LevelSequence { levels }))
+    }
+
+    /// The level played in the given wave, the sequence starts over once every level was played
+    pub(crate) fn for_wave(&self, wave: u32) -> &Level {
+        &self.levels[(wave - 1) as usize % self.levels.len()]
+    }
+}
+
+pub fn levels_directory() -> PathBuf {
+    FileAssetReader::get_base_path()
+        .join("assets")
+        .join(LEVELS_DIRECTORY)
+}
@@ -0,0 +1,231 @@
+//! Game rules and systems, the binary only picks how the game is run
+
+// Bevy systems and queries routinely trip these lints
+#![allow(clippy::too_many_arguments, clippy::type_complexity)]
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+pub mod assets;
+pub mod bullets;
+pub mod cli;
+pub mod collision;
+pub mod config;
+pub mod controls;
+pub mod data_files;
+pub mod game;
+pub mod gamestate;
+pub mod headless;
+pub mod highscores;
+pub mod hud;
+pub mod input;
+pub mod interpolation;
+pub mod invaders;
+pub mod levels;
+pub mod menu;
+pub mod mystery_ship;
+pub mod pause;
+pub mod player;
+pub mod postgame;
+pub mod replay;
+pub mod rng;
+pub mod score;
+pub mod shields;
+pub mod turns;
+pub mod walls;
+pub mod waves;
+
+pub const PLAYER_WIDTH: f32 = 60.0;
+pub const PLAYER_HEIGHT: f32 = 30.0;
+pub const INVADER_SIZE: Vec2 = Vec2::new(40.0, 32.0);
+pub const BULLET_SIZE: Vec2 = Vec2::new(8.0, 16.0);
+const GAP_BETWEEN_PLAYER_AND_FLOOR: f32 = 40.0;
+
+pub const PLAYER_Y: f32 = walls::BOTTOM_WALL + GAP_BETWEEN_PLAYER_AND_FLOOR;
+
+/// Marks entities that belong to a running round and are cleaned up when it ends
+#[derive(Component)]
+pub struct OnGameScreen;
+
+#[derive(Component)]
+pub struct Player;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub enum InvaderDifficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+#[derive(Component)]
+pub struct Invader {
+    pub difficulty: InvaderDifficulty,
+    pub column: i32,
+}
+
+#[derive(Component)]
+pub struct MysteryShip;
+
+/// A shield, eroded cell by cell by whatever hits it
+#[derive(Component)]
+pub struct Shield {
+    pub mask: shields::ShieldMask,
+}
+
+/// What a hitbox belongs to, this picks the `CollisionEvent` sent when two hitboxes overlap
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum CollisionLayer {
+    Player,
+    Invader,
+    MysteryShip,
+    PlayerBullet,
+    InvaderBullet,
+    Shield,
+    Wall,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum HitboxShape {
+    /// Box of the given width and height
+    Aabb(Vec2),
+    Circle {
+        radius: f32,
+    },
+}
+
+/// Collision shape of an entity, in world units around its translation. The scale of the
+/// `Transform` is ignored.
+#[derive(Component, Clone, Copy, Debug, PartialEq)]
+pub struct Hitbox {
+    pub layer: CollisionLayer,
+    pub shape: HitboxShape,
+    pub offset: Vec2,
+}
+
+impl Hitbox {
+    pub fn aabb(layer: CollisionLayer, size: Vec2) -> Self {
+        Hitbox {
+            layer,
+            shape: HitboxShape::Aabb(size),
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn circle(layer: CollisionLayer, radius: f32) -> Self {
+        Hitbox {
+            layer,
+            shape: HitboxShape::Circle { radius },
+            offset: Vec2::ZERO,
+        }
+    }
+
+    pub fn with_offset(self, offset: Vec2) -> Self {
+        Hitbox { offset, ..self }
+    }
+}
+
+#[derive(Component)]
+pub struct CommonBullet;
+
+#[derive(Component)]
+pub struct PlayerBullet;
+
+#[derive(Event)]
+pub struct PlayerBulletFiredEvent(pub Vec3);
+
+#[derive(Component)]
+pub struct InvaderBullet;
+
+#[derive(Event)]
+pub struct InvaderBulletFiredEvent {
+    pub position: Vec3,
+    pub invader_difficulty: InvaderDifficulty,
+}
+
+#[derive(Event)]
+pub struct InvaderKilledEvent {
+    pub position: Vec3,
+    pub invader_difficulty: InvaderDifficulty,
+}
+
+#[derive(Event)]
+pub struct MysteryShipHitEvent {
+    pub position: Vec3,
+    pub points: u32,
+}
+
+/// Two hitboxes touched during the tick, sent every tick for as long as they do. Events come in
+/// the order the hits happened.
+#[derive(Event, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CollisionEvent {
+    PlayerBulletHitInvader {
+        bullet: Entity,
+        invader: Entity,
+    },
+    PlayerBulletHitMysteryShip {
+        bullet: Entity,
+        mystery_ship: Entity,
+    },
+    InvaderBulletHitPlayer {
+        bullet: Entity,
+        player: Entity,
+    },
+    BulletHitShield {
+        bullet: Entity,
+        shield: Entity,
+    },
+    BulletHitWall {
+        bullet: Entity,
+    },
+    InvaderHitShield {
+        invader: Entity,
+        shield: Entity,
+    },
+    InvaderHitWall {
+        invader: Entity,
+    },
+}
+
+#[derive(Event)]
+pub struct InvadersReachedBottomEvent;
+
+#[derive(Event)]
+pub struct InvaderBulletHitPlayerEvent;
+
+#[derive(Event)]
+pub struct PlayerKilledAllInvadersEvent;
+
+#[derive(Event)]
+pub struct PlayerOutOfLivesEvent;
+
+/// The player in play is done for now and hands the board over to the other player
+#[derive(Event)]
+pub struct TurnOverEvent;
+
+#[derive(Component)]
+pub struct Velocity(pub Vec3);
+
+#[derive(Resource, Default)]
+pub struct Score(pub u32);
+
+/// Number of the formation currently attacking, starting at 1
+#[derive(Resource)]
+pub struct Wave(pub u32);
+
+/// Best score reached since the game was launched
+#[derive(Resource, Default)]
+pub struct HiScore(pub u32);
+
+#[derive(Component)]
+pub struct Ambience;
+
+#[derive(Resource)]
+pub struct Settings {
+    pub sound: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings { sound: true }
+    }
+}
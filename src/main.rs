@@ -1,80 +1,118 @@
 use bevy::prelude::*;
-use bevy_inspector_egui::quick::WorldInspectorPlugin;
 
-pub mod bullets;
-pub mod game;
-pub mod gamestate;
-pub mod invaders;
-pub mod player;
-pub mod shields;
-pub mod walls;
-
-const PLAYER_SPEED: f32 = 500.0;
-const PLAYER_WIDTH: f32 = 60.0;
-const PLAYER_HEIGHT: f32 = 30.0;
-const INVADER_SIZE: f32 = 20.0;
-const GAP_BETWEEN_PLAYER_AND_FLOOR: f32 = 40.0;
-
-const PLAYER_Y: f32 = walls::BOTTOM_WALL + GAP_BETWEEN_PLAYER_AND_FLOOR;
+use space_invaders::{cli, config, controls, game, headless, levels, replay};
 
 fn main() {
-    App::new()
-        .add_plugins(DefaultPlugins)
+    let mut cli_args = match cli::CliArgs::parse(std::env::args().skip(1)) {
+        Ok(cli_args) => cli_args,
+        Err(message) => {
+            eprintln!("{}", message);
+            std::process::exit(2);
+        }
+    };
+    if cli_args.help {
+        println!("{}", cli::USAGE);
+        return;
+    }
+
+    let mut app = App::new();
+    let config_path = config::config_path();
+
+    let game_config = match config::GameConfig::load(&config_path) {
+        Ok(Some(game_config)) => game_config,
+        Ok(None) => {
+            eprintln!(
+                "No game config at {}, using the defaults",
+                config_path.display()
+            );
+            config::GameConfig::default()
+        }
+        Err(message) => {
+            eprintln!(
+                "Invalid game config {}:\n{}",
+                config_path.display(),
+                message
+            );
+            std::process::exit(2);
+        }
+    };
+    app.insert_resource(game_config);
+
+    let levels_directory = levels::levels_directory();
+    let level_sequence = match levels::LevelSequence::load(&levels_directory) {
+        Ok(Some(level_sequence)) => level_sequence,
+        Ok(None) => {
+            eprintln!(
+                "No level sequence in {}, playing the classic formation",
+                levels_directory.display()
+            );
+            levels::LevelSequence::default()
+        }
+        Err(message) => {
+            eprintln!(
+                "Invalid level in {}:\n{}",
+                levels_directory.display(),
+                message
+            );
+            std::process::exit(2);
+        }
+    };
+    app.insert_resource(level_sequence);
+
+    // A broken controls file isn't worth refusing to start, the default keys still work
+    match controls::controls_path() {
+        Some(controls_path) => {
+            match controls::InputMap::load(&controls_path) {
+                Ok(Some(input_map)) => {
+                    app.insert_resource(input_map);
+                }
+                Ok(None) => {}
+                Err(message) => eprintln!(
+                    "Ignoring invalid controls file {}:\n{}",
+                    controls_path.display(),
+                    message
+                ),
+            }
+            app.insert_resource(controls::ControlsPath(controls_path));
+        }
+        None => eprintln!("No data directory available, changed controls won't be kept"),
+    }
+
+    if let Some(path) = &cli_args.replay {
+        let playback = match replay::ReplayPlayback::load(path) {
+            Ok(playback) => playback,
+            Err(message) => {
+                eprintln!("{}", message);
+                std::process::exit(2);
+            }
+        };
+
+        // The round only plays out the same with the recorded seed, tick rate and levels
+        cli_args.seed = Some(playback.replay().seed);
+        cli_args.tick_rate = Some(playback.replay().tick_rate);
+        app.insert_resource(playback.replay().config.clone())
+            .insert_resource(playback.replay().levels.clone())
+            .insert_resource(playback);
+    }
+
+    if let Some(path) = &cli_args.record {
+        app.insert_resource(replay::ReplayRecording::new(path.clone()));
+    }
+
+    // Tuning changes in the middle of a round would make replays desync
+    if cli_args.replay.is_none() && cli_args.record.is_none() {
+        app.insert_resource(config::ConfigWatcher::new(config_path));
+    }
+
+    if cli_args.headless {
+        app.add_plugins((headless::HeadlessPlugins, bevy::log::LogPlugin::default()))
+            .insert_resource(headless::HeadlessRun::new(cli_args.ticks));
+    } else {
+        app.add_plugins(DefaultPlugins);
+    }
+
+    app.insert_resource(cli_args)
         .add_plugins(game::GamePlugin)
-        // .add_plugins(WorldInspectorPlugin::new())
+        // .add_plugins(bevy_inspector_egui::quick::WorldInspectorPlugin::new())
         .run();
 }
-
-#[derive(Component)]
-struct Player;
-
-#[derive(Clone)]
-enum InvaderDifficulty {
-    Easy,
-    Medium,
-    Hard,
-}
-
-#[derive(Component)]
-struct Invader {
-    difficulty: InvaderDifficulty,
-}
-
-#[derive(Component)]
-struct Shield(i32);
-
-#[derive(Component)]
-struct Collider;
-
-#[derive(Component)]
-struct CommonBullet;
-
-#[derive(Component)]
-struct PlayerBullet;
-
-#[derive(Event)]
-struct PlayerBulletFiredEvent(Vec3);
-
-#[derive(Component)]
-struct InvaderBullet;
-
-#[derive(Event)]
-struct InvaderBulletFiredEvent {
-    position: Vec3,
-    invader_difficulty: InvaderDifficulty,
-}
-
-#[derive(Event)]
-struct InvadersReachedBottomEvent;
-
-#[derive(Event)]
-struct InvaderBulletHitPlayerEvent;
-
-#[derive(Event)]
-struct PlayerKilledAllInvadersEvent;
-
-#[derive(Component)]
-struct Velocity(Vec3);
-
-#[derive(Resource)]
-struct ShootSound(Handle<AudioSource>);
@@ -0,0 +1,493 @@
+use bevy::{app::AppExit, prelude::*};
+
+use crate::{
+    controls::{Action, InputMap},
+    game::GameState,
+    highscores::HighScores,
+    pause::{PauseState, RestartRound},
+    player::{LivesConfig, MAX_LIVES},
+    turns::{PlayersConfig, MAX_PLAYERS},
+    waves::WaveConfig,
+    Settings,
+};
+
+const TITLE_FONT_SIZE: f32 = 60.0;
+const BUTTON_FONT_SIZE: f32 = 30.0;
+const TEXT_FONT_SIZE: f32 = 25.0;
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const NORMAL_BUTTON: Color = Color::rgb(0.15, 0.15, 0.15);
+const HOVERED_BUTTON: Color = Color::rgb(0.25, 0.25, 0.25);
+const PRESSED_BUTTON: Color = Color::rgb(0.35, 0.75, 0.35);
+/// The pause menu lets the frozen round show through
+const PAUSE_BACKGROUND: Color = Color::rgba(0.0, 0.0, 0.0, 0.6);
+
+/// Which screen of the main menu or of the pause menu is currently shown
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub(crate) enum MenuState {
+    Main,
+    Options,
+    HighScores,
+    Controls,
+    Pause,
+    #[default]
+    Disabled,
+}
+
+#[derive(Component)]
+pub(crate) struct OnMainMenuScreen;
+
+#[derive(Component)]
+pub(crate) struct OnOptionsScreen;
+
+#[derive(Component)]
+pub(crate) struct OnHighScoresScreen;
+
+#[derive(Component)]
+pub(crate) struct OnControlsScreen;
+
+#[derive(Component)]
+pub(crate) struct OnPauseScreen;
+
+#[derive(Component)]
+pub(crate) enum MenuButtonAction {
+    Play,
+    Options,
+    HighScores,
+    Quit,
+    ToggleSound,
+    CyclePlayers,
+    CycleLives,
+    ToggleRestoreShields,
+    Controls,
+    Rebind(Action),
+    ResetControls,
+    /// Leaves the current screen for the one it was opened from
+    Back,
+    Resume,
+    RestartRound,
+    QuitToMainMenu,
+}
+
+/// Marks the text of an option button so it can be updated in place
+#[derive(Component)]
+pub(crate) enum SettingText {
+    Sound,
+    Players,
+    Lives,
+    RestoreShields,
+}
+
+/// Marks the text of the button showing the keys bound to an action
+#[derive(Component)]
+pub(crate) struct BindingText(Action);
+
+/// The line under the bindings, telling how rebinding went
+#[derive(Component)]
+pub(crate) struct ControlsMessage;
+
+/// Present while the controls screen waits for the key to bind to an action
+#[derive(Resource)]
+pub(crate) struct AwaitingKey(Action);
+
+fn button_style() -> Style {
+    Style {
+        width: Val::Px(250.0),
+        height: Val::Px(60.0),
+        justify_content: JustifyContent::Center,
+        align_items: AlignItems::Center,
+        ..default()
+    }
+}
+
+fn text_style(font_size: f32) -> TextStyle {
+    TextStyle {
+        font_size,
+        color: TEXT_COLOR,
+        ..default()
+    }
+}
+
+fn screen_node() -> NodeBundle {
+    NodeBundle {
+        style: Style {
+            width: Val::Percent(100.0),
+            height: Val::Percent(100.0),
+            flex_direction: FlexDirection::Column,
+            align_items: AlignItems::Center,
+            justify_content: JustifyContent::Center,
+            row_gap: Val::Px(15.0),
+            ..default()
+        },
+        background_color: Color::BLACK.into(),
+        ..default()
+    }
+}
+
+fn spawn_button(parent: &mut ChildBuilder, label: &str, action: MenuButtonAction) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: button_style(),
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                label,
+                text_style(BUTTON_FONT_SIZE),
+            ));
+        });
+}
+
+/// A button whose text is tagged with the given marker, to be updated in place
+fn spawn_setting_button(
+    parent: &mut ChildBuilder,
+    label: String,
+    action: MenuButtonAction,
+    text_marker: impl Component,
+) {
+    parent
+        .spawn((
+            ButtonBundle {
+                style: button_style(),
+                background_color: NORMAL_BUTTON.into(),
+                ..default()
+            },
+            action,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                TextBundle::from_section(label, text_style(BUTTON_FONT_SIZE)),
+                text_marker,
+            ));
+        });
+}
+
+fn on_off(value: bool) -> &'static str {
+    if value {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+fn setting_label(
+    setting_text: &SettingText,
+    settings: &Settings,
+    players_config: &PlayersConfig,
+    lives_config: &LivesConfig,
+    wave_config: &WaveConfig,
+) -> String {
+    match setting_text {
+        SettingText::Sound => format!("Sound: {}", on_off(settings.sound)),
+        SettingText::Players => format!("Players: {}", players_config.players),
+        SettingText::Lives => format!("Lives: {}", lives_config.starting_lives),
+        SettingText::RestoreShields => {
+            format!("Shields: {}", on_off(wave_config.restore_shields))
+        }
+    }
+}
+
+fn binding_label(action: Action, input_map: &InputMap) -> String {
+    let keys: Vec<String> = input_map
+        .keys(action)
+        .iter()
+        .map(|key_code| format!("{:?}", key_code))
+        .collect();
+    format!("{}: {}", action.label(), keys.join(" / "))
+}
+
+pub(crate) fn enter_main_menu(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Main);
+}
+
+pub(crate) fn exit_menu(mut menu_state: ResMut<NextState<MenuState>>) {
+    menu_state.set(MenuState::Disabled);
+}
+
+pub(crate) fn spawn_main_menu(mut commands: Commands) {
+    commands
+        .spawn((screen_node(), OnMainMenuScreen))
+        .with_children(|parent| {
+            parent.spawn(
+                TextBundle::from_section("SPACE INVADERS", text_style(TITLE_FONT_SIZE)).with_style(
+                    Style {
+                        margin: UiRect::bottom(Val::Px(30.0)),
+                        ..default()
+                    },
+                ),
+            );
+            spawn_button(parent, "Start", MenuButtonAction::Play);
+            spawn_button(parent, "Options", MenuButtonAction::Options);
+            spawn_button(parent, "High Scores", MenuButtonAction::HighScores);
+            spawn_button(parent, "Quit", MenuButtonAction::Quit);
+        });
+}
+
+pub(crate) fn spawn_options_menu(
+    mut commands: Commands,
+    settings: Res<Settings>,
+    players_config: Res<PlayersConfig>,
+    lives_config: Res<LivesConfig>,
+    wave_config: Res<WaveConfig>,
+) {
+    commands
+        .spawn((screen_node(), OnOptionsScreen))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "OPTIONS",
+                text_style(TITLE_FONT_SIZE),
+            ));
+            for (setting_text, action) in [
+                (SettingText::Sound, MenuButtonAction::ToggleSound),
+                (SettingText::Players, MenuButtonAction::CyclePlayers),
+                (SettingText::Lives, MenuButtonAction::CycleLives),
+                (
+                    SettingText::RestoreShields,
+                    MenuButtonAction::ToggleRestoreShields,
+                ),
+            ] {
+                let label = setting_label(
+                    &setting_text,
+                    &settings,
+                    &players_config,
+                    &lives_config,
+                    &wave_config,
+                );
+                spawn_setting_button(parent, label, action, setting_text);
+            }
+            spawn_button(parent, "Controls", MenuButtonAction::Controls);
+            spawn_button(parent, "Back", MenuButtonAction::Back);
+        });
+}
+
+pub(crate) fn spawn_high_scores_menu(mut commands: Commands, high_scores: Res<HighScores>) {
+    commands
+        .spawn((screen_node(), OnHighScoresScreen))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "HIGH SCORES",
+                text_style(TITLE_FONT_SIZE),
+            ));
+            if high_scores.entries.is_empty() {
+                parent.spawn(TextBundle::from_section(
+                    "No high scores yet",
+                    text_style(TEXT_FONT_SIZE),
+                ));
+            }
+
+            for (rank, entry) in high_scores.entries.iter().enumerate() {
+                parent.spawn(TextBundle::from_section(
+                    format!("{:>2}. {:<10} {:>6}", rank + 1, entry.name, entry.score),
+                    text_style(TEXT_FONT_SIZE),
+                ));
+            }
+            spawn_button(parent, "Back", MenuButtonAction::Back);
+        });
+}
+
+pub(crate) fn spawn_controls_menu(mut commands: Commands, input_map: Res<InputMap>) {
+    commands
+        .spawn((screen_node(), OnControlsScreen))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "CONTROLS",
+                text_style(TITLE_FONT_SIZE),
+            ));
+            for action in Action::ALL {
+                spawn_setting_button(
+                    parent,
+                    binding_label(action, &input_map),
+                    MenuButtonAction::Rebind(action),
+                    BindingText(action),
+                );
+            }
+            parent.spawn((
+                TextBundle::from_section(
+                    "Click an action, then press its new key",
+                    text_style(TEXT_FONT_SIZE),
+                ),
+                ControlsMessage,
+            ));
+            spawn_button(parent, "Reset", MenuButtonAction::ResetControls);
+            spawn_button(parent, "Back", MenuButtonAction::Back);
+        });
+}
+
+pub(crate) fn stop_awaiting_key(mut commands: Commands) {
+    commands.remove_resource::<AwaitingKey>();
+}
+
+pub(crate) fn spawn_pause_menu(mut commands: Commands) {
+    let mut node = screen_node();
+    node.background_color = PAUSE_BACKGROUND.into();
+
+    commands
+        .spawn((node, OnPauseScreen))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                "PAUSED",
+                text_style(TITLE_FONT_SIZE),
+            ));
+            spawn_button(parent, "Resume", MenuButtonAction::Resume);
+            spawn_button(parent, "Restart Round", MenuButtonAction::RestartRound);
+            spawn_button(parent, "Options", MenuButtonAction::Options);
+            spawn_button(parent, "Main Menu", MenuButtonAction::QuitToMainMenu);
+        });
+}
+
+pub(crate) fn button_colors(
+    mut interaction_query: Query<(&Interaction, &mut BackgroundColor), Changed<Interaction>>,
+) {
+    for (interaction, mut background_color) in interaction_query.iter_mut() {
+        *background_color = match *interaction {
+            Interaction::Pressed => PRESSED_BUTTON.into(),
+            Interaction::Hovered => HOVERED_BUTTON.into(),
+            Interaction::None => NORMAL_BUTTON.into(),
+        };
+    }
+}
+
+pub(crate) fn menu_action(
+    mut commands: Commands,
+    interaction_query: Query<(&Interaction, &MenuButtonAction), Changed<Interaction>>,
+    mut app_exit_events: EventWriter<AppExit>,
+    current_menu_state: Res<State<MenuState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    mut game_state: ResMut<NextState<GameState>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut settings: ResMut<Settings>,
+    mut players_config: ResMut<PlayersConfig>,
+    mut lives_config: ResMut<LivesConfig>,
+    mut wave_config: ResMut<WaveConfig>,
+    mut input_map: ResMut<InputMap>,
+    mut message_query: Query<&mut Text, With<ControlsMessage>>,
+) {
+    for (interaction, menu_button_action) in interaction_query.iter() {
+        if *interaction != Interaction::Pressed {
+            continue;
+        }
+
+        match menu_button_action {
+            MenuButtonAction::Play => {
+                game_state.set(GameState::Game);
+                menu_state.set(MenuState::Disabled);
+            }
+            MenuButtonAction::Options => menu_state.set(MenuState::Options),
+            MenuButtonAction::HighScores => menu_state.set(MenuState::HighScores),
+            MenuButtonAction::Quit => app_exit_events.send(AppExit),
+            MenuButtonAction::ToggleSound => settings.sound = !settings.sound,
+            MenuButtonAction::CyclePlayers => {
+                players_config.players = players_config.players % MAX_PLAYERS + 1;
+            }
+            MenuButtonAction::CycleLives => {
+                lives_config.starting_lives = lives_config.starting_lives % MAX_LIVES + 1;
+            }
+            MenuButtonAction::ToggleRestoreShields => {
+                wave_config.restore_shields = !wave_config.restore_shields;
+            }
+            MenuButtonAction::Controls => menu_state.set(MenuState::Controls),
+            MenuButtonAction::Rebind(action) => {
+                commands.insert_resource(AwaitingKey(*action));
+                for mut text in message_query.iter_mut() {
+                    text.sections[0].value =
+                        format!("Press a key for {}, Esc to cancel", action.label());
+                }
+            }
+            MenuButtonAction::ResetControls => {
+                *input_map = InputMap::default();
+                for mut text in message_query.iter_mut() {
+                    text.sections[0].value = "Controls reset to defaults".to_string();
+                }
+            }
+            MenuButtonAction::Back => match (current_menu_state.get(), pause_state.get()) {
+                (MenuState::Controls, _) => menu_state.set(MenuState::Options),
+                (_, PauseState::Running) => menu_state.set(MenuState::Main),
+                (_, PauseState::Paused) => menu_state.set(MenuState::Pause),
+            },
+            MenuButtonAction::Resume => {
+                next_pause_state.set(PauseState::Running);
+                menu_state.set(MenuState::Disabled);
+            }
+            MenuButtonAction::RestartRound => {
+                commands.insert_resource(RestartRound);
+                next_pause_state.set(PauseState::Running);
+                menu_state.set(MenuState::Disabled);
+            }
+            // Entering the main menu shows its first screen
+            MenuButtonAction::QuitToMainMenu => {
+                next_pause_state.set(PauseState::Running);
+                game_state.set(GameState::MainMenu);
+            }
+        }
+    }
+}
+
+pub(crate) fn update_setting_texts(
+    settings: Res<Settings>,
+    players_config: Res<PlayersConfig>,
+    lives_config: Res<LivesConfig>,
+    wave_config: Res<WaveConfig>,
+    mut text_query: Query<(&mut Text, &SettingText)>,
+) {
+    if !settings.is_changed()
+        && !players_config.is_changed()
+        && !lives_config.is_changed()
+        && !wave_config.is_changed()
+    {
+        return;
+    }
+
+    for (mut text, setting_text) in text_query.iter_mut() {
+        text.sections[0].value = setting_label(
+            setting_text,
+            &settings,
+            &players_config,
+            &lives_config,
+            &wave_config,
+        );
+    }
+}
+
+/// Binds the next key pressed on the controls screen, Esc cancels
+pub(crate) fn capture_binding(
+    mut commands: Commands,
+    keyboard_input: Res<Input<KeyCode>>,
+    awaiting_key: Res<AwaitingKey>,
+    mut input_map: ResMut<InputMap>,
+    mut message_query: Query<&mut Text, With<ControlsMessage>>,
+) {
+    let Some(&key_code) = keyboard_input.get_just_pressed().next() else {
+        return;
+    };
+    commands.remove_resource::<AwaitingKey>();
+
+    let action = awaiting_key.0;
+    let message = if key_code == KeyCode::Escape {
+        "Rebinding cancelled".to_string()
+    } else {
+        match input_map.bind(action, key_code) {
+            Ok(()) => format!("{} bound to {:?}", action.label(), key_code),
+            Err(other) => format!("{:?} is already used for {}", key_code, other.label()),
+        }
+    };
+    for mut text in message_query.iter_mut() {
+        text.sections[0].value = message.clone();
+    }
+}
+
+pub(crate) fn update_binding_texts(
+    input_map: Res<InputMap>,
+    mut text_query: Query<(&mut Text, &BindingText)>,
+) {
+    if !input_map.is_changed() {
+        return;
+    }
+
+    for (mut text, binding_text) in text_query.iter_mut() {
+        text.sections[0].value = binding_label(binding_text.0, &input_map);
+    }
+}
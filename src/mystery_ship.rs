@@ -0,0 +1,169 @@
+use bevy::prelude::*;
+use rand::Rng;
+
+use crate::{
+    assets::GameAssets,
+    config::{GameConfig, MysteryShipTuning},
+    interpolation::InterpolatedPosition,
+    rng::GameRng,
+    walls, CollisionEvent, CollisionLayer, Hitbox, MysteryShip, MysteryShipHitEvent, OnGameScreen,
+    PlayerBullet, PlayerBulletFiredEvent, Settings, Velocity,
+};
+
+const MYSTERY_SHIP_SIZE: Vec2 = Vec2::new(40.0, 20.0);
+const MYSTERY_SHIP_Y: f32 = walls::TOP_WALL - 25.0;
+
+/// Bonus awarded for a hit, picked by how many shots the player fired so far, as in the arcade
+const MYSTERY_SHIP_POINTS: [u32; 15] = [
+    100, 50, 50, 100, 150, 100, 100, 50, 300, 100, 100, 100, 50, 150, 100,
+];
+
+#[derive(Resource)]
+pub(crate) struct MysteryShipConfig {
+    spawn_timer: Timer,
+    shots_fired: usize,
+}
+
+impl MysteryShipConfig {
+    pub(crate) fn new(game_rng: &mut GameRng, config: &GameConfig) -> Self {
+        MysteryShipConfig {
+            spawn_timer: Timer::from_seconds(
+                random_spawn_interval(game_rng, &config.mystery_ship),
+                TimerMode::Once,
+            ),
+            shots_fired: 0,
+        }
+    }
+}
+
+fn random_spawn_interval(game_rng: &mut GameRng, tuning: &MysteryShipTuning) -> f32 {
+    game_rng
+        .gameplay
+        .gen_range(tuning.min_spawn_interval..tuning.max_spawn_interval)
+}
+
+pub(crate) fn setup(
+    mut commands: Commands,
+    mut game_rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+) {
+    commands.insert_resource(MysteryShipConfig::new(&mut game_rng, &config));
+}
+
+pub(crate) fn count_player_shots(
+    mut player_bullet_fired_event: EventReader<PlayerBulletFiredEvent>,
+    mut mystery_ship_config: ResMut<MysteryShipConfig>,
+) {
+    mystery_ship_config.shots_fired += player_bullet_fired_event.read().count();
+}
+
+pub(crate) fn spawn_mystery_ship(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    settings: Res<Settings>,
+    mut mystery_ship_config: ResMut<MysteryShipConfig>,
+    mystery_ship_query: Query<(), With<MysteryShip>>,
+    mut game_rng: ResMut<GameRng>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    // Only one ship flies at a time, the next one is scheduled once it is gone
+    if !mystery_ship_query.is_empty() {
+        return;
+    }
+
+    if !mystery_ship_config
+        .spawn_timer
+        .tick(time.delta())
+        .just_finished()
+    {
+        return;
+    }
+
+    mystery_ship_config.spawn_timer = Timer::from_seconds(
+        random_spawn_interval(&mut game_rng, &config.mystery_ship),
+        TimerMode::Once,
+    );
+
+    let direction = if mystery_ship_config.shots_fired % 2 == 0 {
+        1.0
+    } else {
+        -1.0
+    };
+    let starting_position = Vec3::new(
+        -direction * (walls::RIGHT_WALL - walls::WALL_THICKNESS - MYSTERY_SHIP_SIZE.x),
+        MYSTERY_SHIP_Y,
+        0.0,
+    );
+
+    let mut playback_settings = PlaybackSettings::LOOP;
+    playback_settings.paused = !settings.sound;
+
+    commands.spawn((
+        SpriteBundle {
+            texture: game_assets.mystery_ship.clone(),
+            transform: Transform::from_translation(starting_position),
+            ..default()
+        },
+        AudioBundle {
+            source: game_assets.mystery_ship_sound.clone(),
+            settings: playback_settings,
+        },
+        MysteryShip,
+        Hitbox::aabb(CollisionLayer::MysteryShip, MYSTERY_SHIP_SIZE),
+        InterpolatedPosition::new(starting_position),
+        Velocity(Vec3::new(direction * config.mystery_ship.speed, 0.0, 0.0)),
+        OnGameScreen,
+    ));
+}
+
+pub(crate) fn move_mystery_ship(
+    mut commands: Commands,
+    mut mystery_ship_query: Query<(Entity, &mut Transform, &Velocity), With<MysteryShip>>,
+    time: Res<Time>,
+) {
+    for (mystery_ship, mut transform, velocity) in mystery_ship_query.iter_mut() {
+        transform.translation += velocity.0 * time.delta_seconds();
+
+        // Despawning also stops the flyby loop
+        if transform.translation.x.abs() > walls::RIGHT_WALL {
+            commands.entity(mystery_ship).despawn();
+        }
+    }
+}
+
+pub(crate) fn check_player_bullet_mystery_ship_collision(
+    mut commands: Commands,
+    mut collision_events: EventReader<CollisionEvent>,
+    bullet_query: Query<(), With<PlayerBullet>>,
+    mystery_ship_query: Query<&Transform, With<MysteryShip>>,
+    mystery_ship_config: Res<MysteryShipConfig>,
+    mut mystery_ship_hit_event: EventWriter<MysteryShipHitEvent>,
+) {
+    let mut destroyed = false;
+
+    for event in collision_events.read() {
+        let CollisionEvent::PlayerBulletHitMysteryShip {
+            bullet,
+            mystery_ship,
+        } = *event
+        else {
+            continue;
+        };
+        if destroyed || !bullet_query.contains(bullet) {
+            continue;
+        }
+        let Ok(mystery_ship_transform) = mystery_ship_query.get(mystery_ship) else {
+            continue;
+        };
+
+        commands.entity(bullet).despawn();
+        commands.entity(mystery_ship).despawn();
+        destroyed = true;
+        mystery_ship_hit_event.send(MysteryShipHitEvent {
+            position: mystery_ship_transform.translation,
+            points: MYSTERY_SHIP_POINTS
+                [mystery_ship_config.shots_fired % MYSTERY_SHIP_POINTS.len()],
+        });
+    }
+}
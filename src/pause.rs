@@ -0,0 +1,90 @@
+use bevy::prelude::*;
+
+use crate::{
+    controls::{Action, InputMap},
+    game::GameState,
+    input,
+    menu::MenuState,
+    Ambience, MysteryShip, OnGameScreen,
+};
+
+/// Volume of the game sounds while the pause menu is open
+const DUCKED_VOLUME: f32 = 0.25;
+
+/// Whether the round in progress is paused, only meaningful in `GameState::Game`
+#[derive(Clone, Copy, Default, Eq, PartialEq, Debug, Hash, States)]
+pub enum PauseState {
+    #[default]
+    Running,
+    Paused,
+}
+
+/// Present when the pause menu asked for the round to start over
+#[derive(Resource)]
+pub(crate) struct RestartRound;
+
+pub(crate) fn toggle_pause(
+    keyboard_input: Res<Input<KeyCode>>,
+    input_map: Res<InputMap>,
+    gamepad_buttons: Res<Input<GamepadButton>>,
+    pause_state: Res<State<PauseState>>,
+    mut next_pause_state: ResMut<NextState<PauseState>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+) {
+    if !input_map.just_pressed(Action::Pause, &keyboard_input)
+        && !input::start_just_pressed(&gamepad_buttons)
+    {
+        return;
+    }
+
+    match pause_state.get() {
+        PauseState::Running => next_pause_state.set(PauseState::Paused),
+        PauseState::Paused => {
+            next_pause_state.set(PauseState::Running);
+            menu_state.set(MenuState::Disabled);
+        }
+    }
+}
+
+/// Stops the clock the simulation runs on, so every timer and everything that moves stays where
+/// it is until the game resumes
+pub(crate) fn pause_game(
+    mut time: ResMut<Time<Virtual>>,
+    mut menu_state: ResMut<NextState<MenuState>>,
+    sink_query: Query<&AudioSink, Or<(With<Ambience>, With<MysteryShip>)>>,
+) {
+    time.pause();
+    menu_state.set(MenuState::Pause);
+    for sink in sink_query.iter() {
+        sink.set_volume(DUCKED_VOLUME);
+    }
+}
+
+pub(crate) fn resume_game(
+    mut time: ResMut<Time<Virtual>>,
+    sink_query: Query<&AudioSink, Or<(With<Ambience>, With<MysteryShip>)>>,
+) {
+    time.unpause();
+    for sink in sink_query.iter() {
+        sink.set_volume(1.0);
+    }
+}
+
+/// Throws the round in progress away and sets up a new one, as when the game state enters
+/// `GameState::Game`
+pub(crate) fn restart_round(world: &mut World) {
+    world.remove_resource::<RestartRound>();
+
+    let entities: Vec<Entity> = world
+        .query_filtered::<Entity, With<OnGameScreen>>()
+        .iter(world)
+        .collect();
+    for entity in entities {
+        // Children were already despawned along with their parent
+        if world.get_entity(entity).is_some() {
+            world.entity_mut(entity).despawn_recursive();
+        }
+    }
+
+    world.run_schedule(OnEnter(GameState::Game));
+}
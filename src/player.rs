@@ -2,55 +2,151 @@ use std::time::Duration;
 
 use bevy::prelude::*;
 
-use crate::{walls, Player, PlayerBulletFiredEvent, PLAYER_SPEED, PLAYER_WIDTH, PLAYER_Y};
+use crate::{
+    assets::GameAssets, config::GameConfig, input::PlayerInput,
+    interpolation::InterpolatedPosition, turns::Turns, walls, CollisionLayer, Hitbox,
+    InvaderBulletHitPlayerEvent, OnGameScreen, Player, PlayerBulletFiredEvent,
+    PlayerOutOfLivesEvent, Score, TurnOverEvent, PLAYER_HEIGHT, PLAYER_WIDTH, PLAYER_Y,
+};
 
-const PLAYER_SPRITE_PATH: &str = "player.png";
-const FIRE_RATE: f32 = 0.2;
+const DEATH_FLICKER_RATE: f32 = 0.1;
+const INVULNERABILITY_BLINK_RATE: f32 = 0.15;
+const DEATH_COLOR: Color = Color::rgb(1.0, 0.3, 0.3);
+
+pub(crate) const DEFAULT_LIVES: u32 = 3;
+pub(crate) const MAX_LIVES: u32 = 5;
 
 #[derive(Resource, Debug)]
 pub(crate) struct PlayerShootConfig {
     timer: Timer,
 }
 
-pub(crate) fn setup(mut commands: Commands) {
+#[derive(Resource, Debug)]
+pub(crate) struct LivesConfig {
+    pub(crate) starting_lives: u32,
+    /// Scores at which an extra life is awarded, in ascending order
+    pub(crate) extra_life_scores: Vec<u32>,
+}
+
+impl Default for LivesConfig {
+    fn default() -> Self {
+        LivesConfig {
+            starting_lives: DEFAULT_LIVES,
+            extra_life_scores: vec![1500, 5000],
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub(crate) struct Lives {
+    pub(crate) remaining: u32,
+    next_extra_life: usize,
+}
+
+impl Lives {
+    pub(crate) fn new(lives_config: &LivesConfig) -> Self {
+        Lives {
+            remaining: lives_config.starting_lives,
+            next_extra_life: 0,
+        }
+    }
+}
+
+#[derive(Resource, Debug)]
+pub(crate) struct PlayerRespawnTimer(Timer);
+
+impl PlayerRespawnTimer {
+    pub(crate) fn new(config: &GameConfig) -> Self {
+        PlayerRespawnTimer(Timer::from_seconds(
+            config.player.respawn_delay,
+            TimerMode::Once,
+        ))
+    }
+}
+
+/// The player has been hit and is playing its death animation
+#[derive(Component)]
+pub(crate) struct Dying {
+    timer: Timer,
+    flicker_timer: Timer,
+    flash: bool,
+}
+
+/// The player has just respawned and can't be hit yet
+#[derive(Component)]
+pub(crate) struct Invulnerable {
+    timer: Timer,
+    blink_timer: Timer,
+}
+
+pub(crate) fn setup(
+    mut commands: Commands,
+    lives_config: Res<LivesConfig>,
+    config: Res<GameConfig>,
+) {
     commands.insert_resource(PlayerShootConfig {
-        timer: Timer::new(Duration::from_secs_f32(FIRE_RATE), TimerMode::Once),
+        timer: Timer::new(
+            Duration::from_secs_f32(config.player.fire_rate),
+            TimerMode::Once,
+        ),
     });
+    commands.insert_resource(Lives::new(&lives_config));
+    commands.remove_resource::<PlayerRespawnTimer>();
 }
 
-pub(crate) fn spawn_player(mut commands: Commands, asset_server: Res<AssetServer>) {
-    commands.spawn((
+fn player_bundle(game_assets: &GameAssets) -> impl Bundle {
+    let position = Vec3::new(0.0, PLAYER_Y, 0.0);
+
+    (
         SpriteBundle {
-            texture: asset_server.load(PLAYER_SPRITE_PATH),
+            texture: game_assets.player.clone(),
             transform: Transform {
-                translation: Vec3::new(0.0, PLAYER_Y, 0.0),
+                translation: position,
                 rotation: Quat::IDENTITY,
                 scale: Vec3::ONE,
             },
             ..default()
         },
         Player,
-    ));
+        Hitbox::aabb(
+            CollisionLayer::Player,
+            Vec2::new(PLAYER_WIDTH, PLAYER_HEIGHT),
+        ),
+        InterpolatedPosition::new(position),
+        OnGameScreen,
+    )
+}
+
+pub(crate) fn spawn_player(mut commands: Commands, game_assets: Res<GameAssets>) {
+    commands.spawn(player_bundle(&game_assets));
+}
+
+/// Run condition that is false while the player is dead or waiting to respawn
+pub(crate) fn player_alive(player_query: Query<(), (With<Player>, Without<Dying>)>) -> bool {
+    !player_query.is_empty()
 }
 
 pub(crate) fn move_player(
-    keyboard_input: Res<Input<KeyCode>>,
-    mut query: Query<&mut Transform, With<Player>>,
+    player_input: Res<PlayerInput>,
+    mut query: Query<&mut Transform, (With<Player>, Without<Dying>)>,
+    config: Res<GameConfig>,
     time: Res<Time>,
 ) {
-    let mut player_transform = query.single_mut();
+    let Ok(mut player_transform) = query.get_single_mut() else {
+        return;
+    };
     let mut direction = 0.0;
 
-    if keyboard_input.pressed(KeyCode::Left) || keyboard_input.pressed(KeyCode::A) {
+    if player_input.left {
         direction -= 1.0;
     }
 
-    if keyboard_input.pressed(KeyCode::Right) || keyboard_input.pressed(KeyCode::D) {
+    if player_input.right {
         direction += 1.0;
     }
 
     let new_player_position =
-        player_transform.translation.x + direction * PLAYER_SPEED * time.delta_seconds();
+        player_transform.translation.x + direction * config.player.speed * time.delta_seconds();
 
     let left_bound = walls::LEFT_WALL + walls::WALL_THICKNESS / 2.0 + (PLAYER_WIDTH / 2.0);
     let right_bound = walls::RIGHT_WALL - walls::WALL_THICKNESS / 2.0 - (PLAYER_WIDTH / 2.0);
@@ -59,25 +155,167 @@ pub(crate) fn move_player(
 }
 
 pub(crate) fn shoot(
-    keyboard_input: Res<Input<KeyCode>>,
-    player_query: Query<&Transform, With<Player>>,
+    player_input: Res<PlayerInput>,
+    player_query: Query<&Transform, (With<Player>, Without<Dying>)>,
     mut shoot_config: ResMut<PlayerShootConfig>,
+    config: Res<GameConfig>,
     time: Res<Time>,
     mut player_bullet_fired_event: EventWriter<PlayerBulletFiredEvent>,
 ) {
+    shoot_config
+        .timer
+        .set_duration(Duration::from_secs_f32(config.player.fire_rate));
     shoot_config.timer.tick(time.delta());
 
     if !shoot_config.timer.finished() {
         return;
     }
 
-    if keyboard_input.pressed(KeyCode::Space) {
-        shoot_config.timer.reset();
+    let Ok(player_transform) = player_query.get_single() else {
+        return;
+    };
 
-        let player_transform = player_query.single();
+    if player_input.fire {
+        shoot_config.timer.reset();
 
         player_bullet_fired_event.send(PlayerBulletFiredEvent(
             player_transform.translation + Vec3::new(0.0, 10.0, 0.0),
         ));
     }
 }
+
+pub(crate) fn kill_player(
+    mut commands: Commands,
+    mut invader_bullet_hit_player_event: EventReader<InvaderBulletHitPlayerEvent>,
+    player_query: Query<Entity, (With<Player>, Without<Dying>)>,
+    mut lives: ResMut<Lives>,
+    config: Res<GameConfig>,
+) {
+    if invader_bullet_hit_player_event.read().next().is_none() {
+        return;
+    }
+
+    let Ok(player) = player_query.get_single() else {
+        return;
+    };
+
+    lives.remaining = lives.remaining.saturating_sub(1);
+
+    commands
+        .entity(player)
+        .remove::<Invulnerable>()
+        .insert(Dying {
+            timer: Timer::from_seconds(config.player.death_animation_time, TimerMode::Once),
+            flicker_timer: Timer::from_seconds(DEATH_FLICKER_RATE, TimerMode::Repeating),
+            flash: false,
+        });
+}
+
+pub(crate) fn animate_dying_player(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Dying, &mut Sprite), With<Player>>,
+    lives: Res<Lives>,
+    turns: Option<Res<Turns>>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+    mut player_out_of_lives_event: EventWriter<PlayerOutOfLivesEvent>,
+    mut turn_over_event: EventWriter<TurnOverEvent>,
+) {
+    for (player, mut dying, mut sprite) in player_query.iter_mut() {
+        dying.timer.tick(time.delta());
+        dying.flicker_timer.tick(time.delta());
+
+        if dying.flicker_timer.just_finished() {
+            dying.flash = !dying.flash;
+        }
+
+        sprite.color = if dying.flash {
+            DEATH_COLOR
+        } else {
+            Color::WHITE
+        }
+        .with_a(dying.timer.percent_left());
+
+        if !dying.timer.finished() {
+            continue;
+        }
+
+        commands.entity(player).despawn();
+
+        // In a two-player round every death hands over to the other player while they have lives
+        if turns
+            .as_ref()
+            .is_some_and(|turns| turns.waiting_player_can_play())
+        {
+            turn_over_event.send(TurnOverEvent);
+        } else if lives.remaining == 0 {
+            player_out_of_lives_event.send(PlayerOutOfLivesEvent);
+        } else {
+            commands.insert_resource(PlayerRespawnTimer::new(&config));
+        }
+    }
+}
+
+pub(crate) fn respawn_player(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    respawn_timer: Option<ResMut<PlayerRespawnTimer>>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
+) {
+    let Some(mut respawn_timer) = respawn_timer else {
+        return;
+    };
+
+    if !respawn_timer.0.tick(time.delta()).finished() {
+        return;
+    }
+
+    commands.remove_resource::<PlayerRespawnTimer>();
+    commands.spawn((
+        player_bundle(&game_assets),
+        Invulnerable {
+            timer: Timer::from_seconds(config.player.invulnerability_time, TimerMode::Once),
+            blink_timer: Timer::from_seconds(INVULNERABILITY_BLINK_RATE, TimerMode::Repeating),
+        },
+    ));
+}
+
+pub(crate) fn tick_invulnerability(
+    mut commands: Commands,
+    mut player_query: Query<(Entity, &mut Invulnerable, &mut Visibility), With<Player>>,
+    time: Res<Time>,
+) {
+    for (player, mut invulnerable, mut visibility) in player_query.iter_mut() {
+        invulnerable.timer.tick(time.delta());
+        invulnerable.blink_timer.tick(time.delta());
+
+        if invulnerable.timer.finished() {
+            *visibility = Visibility::Inherited;
+            commands.entity(player).remove::<Invulnerable>();
+            continue;
+        }
+
+        if invulnerable.blink_timer.just_finished() {
+            *visibility = match *visibility {
+                Visibility::Hidden => Visibility::Inherited,
+                _ => Visibility::Hidden,
+            };
+        }
+    }
+}
+
+pub(crate) fn award_extra_lives(
+    score: Res<Score>,
+    lives_config: Res<LivesConfig>,
+    mut lives: ResMut<Lives>,
+) {
+    while let Some(&extra_life_score) = lives_config.extra_life_scores.get(lives.next_extra_life) {
+        if score.0 < extra_life_score {
+            return;
+        }
+
+        lives.next_extra_life += 1;
+        lives.remaining += 1;
+    }
+}
@@ -0,0 +1,214 @@
+use bevy::prelude::*;
+
+use crate::{
+    game::GameState,
+    gamestate::GameResult,
+    highscores::{self, HighScoreEntry, HighScores, MAX_NAME_LENGTH},
+    replay::ReplayPlayback,
+    rng::GameRng,
+    turns::Turns,
+    Score,
+};
+
+const TITLE_FONT_SIZE: f32 = 60.0;
+const TEXT_FONT_SIZE: f32 = 25.0;
+const TEXT_COLOR: Color = Color::rgb(0.9, 0.9, 0.9);
+const RESTART_PROMPT: &str = "Press Enter to play again or M for the main menu";
+const DEFAULT_NAME: &str = "PLAYER";
+
+#[derive(Component)]
+pub(crate) struct OnPostGameScreen;
+
+/// The line at the bottom of the result screen, used for the name entry and the restart hint
+#[derive(Component)]
+pub(crate) struct PromptText;
+
+/// Present while players type names for new high scores, one score after the other
+#[derive(Resource)]
+pub(crate) struct NameEntry {
+    name: String,
+    /// Scores still waiting for a name, the one being named first. The player who made the score
+    /// is only set in two-player rounds.
+    scores: Vec<(Option<usize>, u32)>,
+    /// Typing only starts once every key held when the round ended was let go, a movement key
+    /// still held would otherwise type its repeats into the name
+    keys_released: bool,
+}
+
+impl NameEntry {
+    fn prompt(&self) -> String {
+        match self.scores.first() {
+            Some((Some(player), _)) => format!(
+                "PLAYER {} HIGH SCORE! Enter your name: {}_",
+                player + 1,
+                self.name
+            ),
+            _ => format!("NEW HIGH SCORE! Enter your name: {}_", self.name),
+        }
+    }
+}
+
+pub(crate) fn spawn_result_screen(
+    mut commands: Commands,
+    game_result: Res<GameResult>,
+    score: Res<Score>,
+    high_scores: Res<HighScores>,
+    game_rng: Res<GameRng>,
+    playback: Option<Res<ReplayPlayback>>,
+    turns: Option<Res<Turns>>,
+) {
+    let mut name_entry = NameEntry {
+        name: String::new(),
+        keys_released: false,
+        scores: match turns.as_deref() {
+            Some(turns) => turns
+                .scores(&score)
+                .into_iter()
+                .enumerate()
+                .map(|(player, score)| (Some(player), score))
+                .collect(),
+            None => vec![(None, score.0)],
+        },
+    };
+    // Replayed rounds were already played, they don't earn a place in the table
+    name_entry
+        .scores
+        .retain(|(_, score)| playback.is_none() && high_scores.qualifies(*score));
+
+    let prompt = if name_entry.scores.is_empty() {
+        RESTART_PROMPT.to_string()
+    } else {
+        let prompt = name_entry.prompt();
+        commands.insert_resource(name_entry);
+        prompt
+    };
+
+    commands
+        .spawn((
+            NodeBundle {
+                style: Style {
+                    width: Val::Percent(100.0),
+                    height: Val::Percent(100.0),
+                    flex_direction: FlexDirection::Column,
+                    align_items: AlignItems::Center,
+                    justify_content: JustifyContent::Center,
+                    row_gap: Val::Px(20.0),
+                    ..default()
+                },
+                background_color: Color::rgba(0.0, 0.0, 0.0, 0.6).into(),
+                ..default()
+            },
+            OnPostGameScreen,
+        ))
+        .with_children(|parent| {
+            parent.spawn(TextBundle::from_section(
+                game_result.title(),
+                TextStyle {
+                    font_size: TITLE_FONT_SIZE,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                game_result.description(),
+                TextStyle {
+                    font_size: TEXT_FONT_SIZE,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ));
+            parent.spawn(TextBundle::from_section(
+                match turns.as_deref() {
+                    Some(turns) => {
+                        let [player_one, player_two] = turns.scores(&score);
+                        format!(
+                            "Player 1: {}    Player 2: {}    Seed: {}",
+                            player_one, player_two, game_rng.seed
+                        )
+                    }
+                    None => format!("Score: {}    Seed: {}", score.0, game_rng.seed),
+                },
+                TextStyle {
+                    font_size: TEXT_FONT_SIZE,
+                    color: TEXT_COLOR,
+                    ..default()
+                },
+            ));
+            parent.spawn((
+                TextBundle::from_section(
+                    prompt,
+                    TextStyle {
+                        font_size: TEXT_FONT_SIZE,
+                        color: TEXT_COLOR,
+                        ..default()
+                    },
+                ),
+                PromptText,
+            ));
+        });
+}
+
+pub(crate) fn restart(
+    keyboard_input: Res<Input<KeyCode>>,
+    mut next_state: ResMut<NextState<GameState>>,
+) {
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        next_state.set(GameState::Game);
+    } else if keyboard_input.just_pressed(KeyCode::M) {
+        next_state.set(GameState::MainMenu);
+    }
+}
+
+pub(crate) fn enter_name(
+    mut commands: Commands,
+    mut received_characters: EventReader<ReceivedCharacter>,
+    keyboard_input: Res<Input<KeyCode>>,
+    mut name_entry: ResMut<NameEntry>,
+    mut high_scores: ResMut<HighScores>,
+    mut prompt_query: Query<&mut Text, With<PromptText>>,
+) {
+    if !name_entry.keys_released {
+        received_characters.clear();
+        name_entry.keys_released = keyboard_input.get_pressed().next().is_none();
+        return;
+    }
+
+    for received_character in received_characters.read() {
+        let character = received_character.char;
+        // Names don't start with a space
+        let accepted =
+            character.is_ascii_alphanumeric() || (character == ' ' && !name_entry.name.is_empty());
+        if accepted && name_entry.name.len() < MAX_NAME_LENGTH {
+            name_entry.name.push(character.to_ascii_uppercase());
+        }
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Back) {
+        name_entry.name.pop();
+    }
+
+    if keyboard_input.just_pressed(KeyCode::Return) {
+        let name = name_entry.name.trim();
+        let name = if name.is_empty() { DEFAULT_NAME } else { name }.to_string();
+        let (_, score) = name_entry.scores.remove(0);
+        high_scores.insert(HighScoreEntry { name, score });
+        highscores::save_high_scores(&high_scores);
+
+        // The score just entered may have pushed the next one out of the table
+        name_entry.name.clear();
+        name_entry
+            .scores
+            .retain(|(_, score)| high_scores.qualifies(*score));
+    }
+
+    let prompt = if name_entry.scores.is_empty() {
+        commands.remove_resource::<NameEntry>();
+        RESTART_PROMPT.to_string()
+    } else {
+        name_entry.prompt()
+    };
+
+    for mut text in prompt_query.iter_mut() {
+        text.sections[0].value = prompt.clone();
+    }
+}
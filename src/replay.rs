@@ -0,0 +1,240 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    cli::{self, CliArgs},
+    config::GameConfig,
+    data_files::{self, VersionedFile},
+    input::PlayerInput,
+    levels::LevelSequence,
+    player::LivesConfig,
+    rng::GameRng,
+    turns::PlayersConfig,
+    waves::WaveConfig,
+    Score,
+};
+
+/// A recorded round, holding everything needed to play it back tick for tick
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq)]
+pub struct Replay {
+    version: u32,
+    pub seed: u64,
+    pub tick_rate: f64,
+    /// Tuning the round was played with, any other values would change how it plays out
+    pub config: GameConfig,
+    pub levels: LevelSequence,
+    players: u32,
+    starting_lives: u32,
+    restore_shields: bool,
+    /// Run-length encoded inputs as (input bits, number of ticks)
+    inputs: Vec<(u8, u32)>,
+    ticks: u32,
+    score: u32,
+}
+
+impl VersionedFile for Replay {
+    const VERSION: u32 = 5;
+
+    fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl Replay {
+    fn load(path: &Path) -> Result<Self, String> {
+        let contents = fs::read_to_string(path).map_err(|error| error.to_string())?;
+        data_files::from_ron(&contents)
+    }
+}
+
+/// Present when the round is driven by a replay file instead of the keyboard
+#[derive(Resource)]
+pub struct ReplayPlayback {
+    replay: Replay,
+    tick: u32,
+    run: usize,
+    run_tick: u32,
+    desync_reported: bool,
+}
+
+impl ReplayPlayback {
+    pub fn load(path: &Path) -> Result<Self, String> {
+        let replay = Replay::load(path)
+            .map_err(|error| format!("could not load replay {}: {}", path.display(), error))?;
+
+        Ok(ReplayPlayback {
+            replay,
+            tick: 0,
+            run: 0,
+            run_tick: 0,
+            desync_reported: false,
+        })
+    }
+
+    pub fn replay(&self) -> &Replay {
+        &self.replay
+    }
+
+    pub(crate) fn matches(&self, score: &Score) -> bool {
+        self.tick == self.replay.ticks && score.0 == self.replay.score
+    }
+
+    fn next_input(&mut self) -> Option<PlayerInput> {
+        let &(bits, length) = self.replay.inputs.get(self.run)?;
+
+        self.tick += 1;
+        self.run_tick += 1;
+        if self.run_tick == length {
+            self.run += 1;
+            self.run_tick = 0;
+        }
+
+        Some(PlayerInput::from_bits(bits))
+    }
+}
+
+/// Present when the inputs of every round are written to a replay file
+#[derive(Resource)]
+pub struct ReplayRecording {
+    path: PathBuf,
+    players: u32,
+    starting_lives: u32,
+    restore_shields: bool,
+    inputs: Vec<(u8, u32)>,
+    ticks: u32,
+}
+
+impl ReplayRecording {
+    pub fn new(path: PathBuf) -> Self {
+        ReplayRecording {
+            path,
+            players: 0,
+            starting_lives: 0,
+            restore_shields: false,
+            inputs: Vec::new(),
+            ticks: 0,
+        }
+    }
+}
+
+pub(crate) fn apply_replay_settings(
+    playback: Res<ReplayPlayback>,
+    mut players_config: ResMut<PlayersConfig>,
+    mut lives_config: ResMut<LivesConfig>,
+    mut wave_config: ResMut<WaveConfig>,
+) {
+    players_config.players = playback.replay.players;
+    lives_config.starting_lives = playback.replay.starting_lives;
+    wave_config.restore_shields = playback.replay.restore_shields;
+}
+
+pub(crate) fn start_playback(mut playback: ResMut<ReplayPlayback>) {
+    playback.tick = 0;
+    playback.run = 0;
+    playback.run_tick = 0;
+    playback.desync_reported = false;
+}
+
+pub(crate) fn start_recording(
+    mut recording: ResMut<ReplayRecording>,
+    players_config: Res<PlayersConfig>,
+    lives_config: Res<LivesConfig>,
+    wave_config: Res<WaveConfig>,
+) {
+    recording.players = players_config.players;
+    recording.starting_lives = lives_config.starting_lives;
+    recording.restore_shields = wave_config.restore_shields;
+    recording.inputs.clear();
+    recording.ticks = 0;
+}
+
+pub(crate) fn play_back_input(
+    mut playback: ResMut<ReplayPlayback>,
+    mut player_input: ResMut<PlayerInput>,
+) {
+    if let Some(input) = playback.next_input() {
+        *player_input = input;
+        return;
+    }
+
+    *player_input = PlayerInput::default();
+
+    if !playback.desync_reported {
+        playback.desync_reported = true;
+        error!(
+            "Replay desynced: the recording ended after {} ticks but the round is still going",
+            playback.replay.ticks
+        );
+    }
+}
+
+pub(crate) fn record_input(mut recording: ResMut<ReplayRecording>, player_input: Res<PlayerInput>) {
+    let bits = player_input.to_bits();
+
+    recording.ticks += 1;
+    match recording.inputs.last_mut() {
+        Some((last_bits, length)) if *last_bits == bits => *length += 1,
+        _ => recording.inputs.push((bits, 1)),
+    }
+}
+
+pub(crate) fn save_recording(
+    recording: Res<ReplayRecording>,
+    cli_args: Res<CliArgs>,
+    game_rng: Res<GameRng>,
+    config: Res<GameConfig>,
+    levels: Res<LevelSequence>,
+    score: Res<Score>,
+) {
+    let replay = Replay {
+        version: Replay::VERSION,
+        seed: game_rng.seed,
+        tick_rate: cli_args.tick_rate.unwrap_or(cli::DEFAULT_TICK_RATE),
+        config: config.clone(),
+        levels: levels.clone(),
+        players: recording.players,
+        starting_lives: recording.starting_lives,
+        restore_shields: recording.restore_shields,
+        inputs: recording.inputs.clone(),
+        ticks: recording.ticks,
+        score: score.0,
+    };
+
+    let result = ron::to_string(&replay)
+        .map_err(|error| error.to_string())
+        .and_then(|contents| {
+            fs::write(&recording.path, contents).map_err(|error| error.to_string())
+        });
+
+    match result {
+        Ok(()) => info!("Replay saved to {}", recording.path.display()),
+        Err(error) => error!(
+            "Could not save replay to {}: {}",
+            recording.path.display(),
+            error
+        ),
+    }
+}
+
+pub(crate) fn check_playback(mut playback: ResMut<ReplayPlayback>, score: Res<Score>) {
+    let replay = &playback.replay;
+
+    if playback.matches(&score) {
+        info!(
+            "Replay finished: score {} after {} ticks matches the recording",
+            score.0, playback.tick
+        );
+    } else {
+        error!(
+            "Replay desynced: recorded score {} after {} ticks, played back score {} after {} ticks",
+            replay.score, replay.ticks, score.0, playback.tick
+        );
+    }
+
+    playback.desync_reported = true;
+}
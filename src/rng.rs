@@ -0,0 +1,50 @@
+use bevy::prelude::*;
+use rand::{RngCore, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::cli::CliArgs;
+
+const GAMEPLAY_STREAM: u64 = 0;
+const EFFECTS_STREAM: u64 = 1;
+
+/// The only source of randomness in the game.
+///
+/// Gameplay systems draw from `gameplay` so a seed and the player's inputs fully determine a
+/// round. Cosmetic effects like sound variations use their own stream so that toggling them
+/// can't change the game.
+#[derive(Resource)]
+pub(crate) struct GameRng {
+    pub(crate) seed: u64,
+    pub(crate) gameplay: ChaCha8Rng,
+    pub(crate) effects: ChaCha8Rng,
+}
+
+impl GameRng {
+    pub(crate) fn new(seed: u64) -> Self {
+        let mut gameplay = ChaCha8Rng::seed_from_u64(seed);
+        gameplay.set_stream(GAMEPLAY_STREAM);
+        let mut effects = ChaCha8Rng::seed_from_u64(seed);
+        effects.set_stream(EFFECTS_STREAM);
+
+        GameRng {
+            seed,
+            gameplay,
+            effects,
+        }
+    }
+}
+
+impl Default for GameRng {
+    fn default() -> Self {
+        GameRng::new(0)
+    }
+}
+
+pub(crate) fn reseed(cli_args: Res<CliArgs>, mut game_rng: ResMut<GameRng>) {
+    let seed = cli_args
+        .seed
+        .unwrap_or_else(|| rand::rngs::OsRng.next_u64());
+
+    info!("Game seed: {}", seed);
+    *game_rng = GameRng::new(seed);
+}
@@ -0,0 +1,75 @@
+use bevy::prelude::*;
+
+use crate::{HiScore, InvaderKilledEvent, MysteryShipHitEvent, OnGameScreen, Score};
+
+const POPUP_FONT_SIZE: f32 = 18.0;
+const POPUP_COLOR: Color = Color::rgb(1.0, 1.0, 0.6);
+const POPUP_LIFETIME: f32 = 0.6;
+const POPUP_SPEED: f32 = 40.0;
+
+/// Floating label showing the points earned for a kill
+#[derive(Component)]
+pub(crate) struct PointsPopup(Timer);
+
+pub(crate) fn setup(mut commands: Commands) {
+    commands.insert_resource(Score::default());
+}
+
+fn spawn_points_popup(commands: &mut Commands, points: u32, position: Vec3) {
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                points.to_string(),
+                TextStyle {
+                    font_size: POPUP_FONT_SIZE,
+                    color: POPUP_COLOR,
+                    ..default()
+                },
+            ),
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        PointsPopup(Timer::from_seconds(POPUP_LIFETIME, TimerMode::Once)),
+        OnGameScreen,
+    ));
+}
+
+pub(crate) fn award_points(
+    mut commands: Commands,
+    mut invader_killed_event: EventReader<InvaderKilledEvent>,
+    mut mystery_ship_hit_event: EventReader<MysteryShipHitEvent>,
+    mut score: ResMut<Score>,
+    mut hi_score: ResMut<HiScore>,
+) {
+    for event in invader_killed_event.read() {
+        let points = event.invader_difficulty.get_points();
+        score.0 += points;
+        spawn_points_popup(&mut commands, points, event.position);
+    }
+
+    for event in mystery_ship_hit_event.read() {
+        score.0 += event.points;
+        spawn_points_popup(&mut commands, event.points, event.position);
+    }
+
+    hi_score.0 = hi_score.0.max(score.0);
+}
+
+pub(crate) fn animate_points_popups(
+    mut commands: Commands,
+    mut popup_query: Query<(Entity, &mut PointsPopup, &mut Transform, &mut Text)>,
+    time: Res<Time>,
+) {
+    for (popup, mut popup_timer, mut transform, mut text) in popup_query.iter_mut() {
+        if popup_timer.0.tick(time.delta()).finished() {
+            commands.entity(popup).despawn();
+            continue;
+        }
+
+        transform.translation.y += POPUP_SPEED * time.delta_seconds();
+        text.sections[0]
+            .style
+            .color
+            .set_a(popup_timer.0.percent_left());
+    }
+}
@@ -1,85 +1,292 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    render::render_resource::{Extent3d, TextureDimension, TextureFormat},
+};
 
-use crate::{walls, CommonBullet, Shield};
+use crate::{
+    config::GameConfig, levels::LevelSequence, walls, CollisionEvent, CollisionLayer, CommonBullet,
+    Hitbox, Invader, OnGameScreen, Shield, Velocity,
+};
 
-const SHIELD_SIZE: f32 = 30.0;
+/// Shield row used by levels that don't place their own shields
+const DEFAULT_SHIELD_POSITIONS: [f32; 4] = [-200.0, -50.0, 100.0, 250.0];
+const SHIELD_Y: f32 = walls::BOTTOM_WALL + 100.0;
 
-impl Shield {
-    fn get_sprite_path(&self) -> String {
-        String::from(match self.0 {
-            3 => "shield-0.png",
-            2 => "shield-1.png",
-            1 => "shield-2.png",
-            0 => "shield-3.png",
-            _ => "shield-0.png",
-        })
+/// Size of a shield in cells, one cell covers one pixel of the arena
+pub const SHIELD_WIDTH: usize = 60;
+pub const SHIELD_HEIGHT: usize = 30;
+/// The legs of an intact shield, below the solid top part
+const SHIELD_LEG_WIDTH: usize = 5;
+const SHIELD_LEG_HEIGHT: usize = 16;
+const SHIELD_COLOR: [u8; 4] = [201, 86, 239, 255];
+
+/// Shape of the crater left by a bullet, `#` cells are removed. It is scaled to the crater size
+/// from the game config.
+#[rustfmt::skip]
+const CRATER_STAMP: [&str; 8] = [
+    "..#..#..",
+    ".######.",
+    "########",
+    ".#######",
+    "#######.",
+    "########",
+    ".######.",
+    "..#..#..",
+];
+
+/// Solid cells of a shield, row 0 is the top of the shield
+#[derive(Clone, Debug, PartialEq)]
+pub struct ShieldMask {
+    cells: Vec<bool>,
+}
+
+impl Default for ShieldMask {
+    /// An intact shield
+    fn default() -> Self {
+        let cells = (0..SHIELD_HEIGHT)
+            .flat_map(|row| {
+                (0..SHIELD_WIDTH).map(move |column| {
+                    row < SHIELD_HEIGHT - SHIELD_LEG_HEIGHT
+                        || !(SHIELD_LEG_WIDTH..SHIELD_WIDTH - SHIELD_LEG_WIDTH).contains(&column)
+                })
+            })
+            .collect();
+
+        ShieldMask { cells }
     }
 }
 
-pub(crate) fn spawn_shields(mut commands: Commands, asset_server: Res<AssetServer>) {
-    let n_cols = 4;
-    let height = walls::BOTTOM_WALL + 100.0;
-    let width = (-walls::LEFT_WALL + walls::RIGHT_WALL) / 1.5;
-    let horizontal_spacing = width / n_cols as f32;
-    let starting_x = -width / (n_cols - 1) as f32;
-    let starting_position = Vec3::new(starting_x, height, 0.0);
-
-    let mut shield_position = starting_position;
-
-    (0..n_cols).for_each(|_column| {
-        let shield: Shield = Shield(3);
-        let shield_sprite_path = shield.get_sprite_path();
-        commands.spawn((
-            shield,
-            SpriteBundle {
-                texture: asset_server.load(shield_sprite_path),
-                transform: Transform {
-                    translation: shield_position,
-                    ..default()
-                },
-                ..default()
+impl ShieldMask {
+    /// Cells outside the shield are never solid
+    pub fn is_solid(&self, column: i32, row: i32) -> bool {
+        self.index(column, row)
+            .is_some_and(|index| self.cells[index])
+    }
+
+    pub fn solid_cells(&self) -> usize {
+        self.cells.iter().filter(|solid| **solid).count()
+    }
+
+    fn index(&self, column: i32, row: i32) -> Option<usize> {
+        let in_bounds =
+            (0..SHIELD_WIDTH as i32).contains(&column) && (0..SHIELD_HEIGHT as i32).contains(&row);
+        in_bounds.then(|| row as usize * SHIELD_WIDTH + column as usize)
+    }
+
+    /// Removes the cells under the crater stamp centered on the given cell
+    fn carve(&mut self, column: i32, row: i32, size: f32) {
+        let size_in_cells = size.round().max(1.0) as i32;
+        let stamp_height = CRATER_STAMP.len() as f32;
+        let stamp_width = CRATER_STAMP[0].len() as f32;
+
+        for dy in 0..size_in_cells {
+            for dx in 0..size_in_cells {
+                let stamp_row = (dy as f32 / size_in_cells as f32 * stamp_height) as usize;
+                let stamp_column = (dx as f32 / size_in_cells as f32 * stamp_width) as usize;
+                if CRATER_STAMP[stamp_row].as_bytes()[stamp_column] != b'#' {
+                    continue;
+                }
+
+                let target = self.index(
+                    column + dx - size_in_cells / 2,
+                    row + dy - size_in_cells / 2,
+                );
+                if let Some(index) = target {
+                    self.cells[index] = false;
+                }
+            }
+        }
+    }
+
+    /// Removes every cell in the given columns and rows
+    fn clear(&mut self, columns: (i32, i32), rows: (i32, i32)) {
+        for row in rows.0..=rows.1 {
+            for column in columns.0..=columns.1 {
+                if let Some(index) = self.index(column, row) {
+                    self.cells[index] = false;
+                }
+            }
+        }
+    }
+
+    /// The first solid cell met by something covering the given columns and rows, scanning rows
+    /// from the bottom when it moves up and from the top when it moves down
+    fn first_hit(
+        &self,
+        columns: (i32, i32),
+        rows: (i32, i32),
+        moving_up: bool,
+    ) -> Option<(i32, i32)> {
+        let center = (columns.0 + columns.1) / 2;
+        let mut row_order: Vec<i32> = (rows.0..=rows.1).collect();
+        if moving_up {
+            row_order.reverse();
+        }
+
+        row_order.into_iter().find_map(|row| {
+            (columns.0..=columns.1)
+                .filter(|column| self.is_solid(*column, row))
+                .min_by_key(|column| (column - center).abs())
+                .map(|column| (column, row))
+        })
+    }
+
+    fn to_image(&self) -> Image {
+        let data = self
+            .cells
+            .iter()
+            .flat_map(|solid| if *solid { SHIELD_COLOR } else { [0; 4] })
+            .collect();
+
+        Image::new(
+            Extent3d {
+                width: SHIELD_WIDTH as u32,
+                height: SHIELD_HEIGHT as u32,
+                depth_or_array_layers: 1,
             },
-        ));
-        shield_position.x += horizontal_spacing;
-    });
+            TextureDimension::D2,
+            data,
+            TextureFormat::Rgba8UnormSrgb,
+        )
+    }
+}
+
+/// Columns and rows of the shield mask covered by an area of the arena, as inclusive ranges
+fn covered_cells(shield_position: Vec3, area: Rect) -> ((i32, i32), (i32, i32)) {
+    let left = area.min.x - (shield_position.x - SHIELD_WIDTH as f32 / 2.0);
+    // Rows grow downwards from the top of the shield
+    let top = shield_position.y + SHIELD_HEIGHT as f32 / 2.0 - area.max.y;
+    (
+        (left.floor() as i32, (left + area.width()).ceil() as i32 - 1),
+        (top.floor() as i32, (top + area.height()).ceil() as i32 - 1),
+    )
+}
+
+pub(crate) fn spawn_shields(mut commands: Commands, levels: Res<LevelSequence>) {
+    spawn_shield_row(&mut commands, &levels, 1);
+}
+
+/// Spawns the intact shields of the wave's level, their sprites are drawn from their masks
+pub(crate) fn spawn_shield_row(commands: &mut Commands, levels: &LevelSequence, wave: u32) {
+    for position in shield_row(levels, wave) {
+        spawn_shield(commands, ShieldMask::default(), position);
+    }
+}
+
+/// Where the shields of the wave's level stand
+pub(crate) fn shield_row(levels: &LevelSequence, wave: u32) -> Vec<Vec3> {
+    let level = levels.for_wave(wave);
+    let positions = level
+        .shields
+        .as_deref()
+        .unwrap_or(&DEFAULT_SHIELD_POSITIONS);
+
+    positions
+        .iter()
+        .map(|&x| Vec3::new(x, SHIELD_Y, 0.0))
+        .collect()
+}
+
+pub(crate) fn spawn_shield(commands: &mut Commands, mask: ShieldMask, position: Vec3) {
+    commands.spawn((
+        Shield { mask },
+        Hitbox::aabb(
+            CollisionLayer::Shield,
+            Vec2::new(SHIELD_WIDTH as f32, SHIELD_HEIGHT as f32),
+        ),
+        SpriteBundle {
+            transform: Transform::from_translation(position),
+            ..default()
+        },
+        OnGameScreen,
+    ));
 }
 
 pub(crate) fn check_bullet_shield_collision(
     mut commands: Commands,
-    bullet_query: Query<(Entity, &Transform), With<CommonBullet>>,
-    shield_query: Query<(Entity, &Transform, &mut Shield), With<Shield>>,
-    asset_server: Res<AssetServer>,
+    mut collision_events: EventReader<CollisionEvent>,
+    bullet_query: Query<(&Transform, &Hitbox, &Velocity), With<CommonBullet>>,
+    mut shield_query: Query<(&Transform, &mut Shield)>,
+    config: Res<GameConfig>,
+    time: Res<Time>,
 ) {
-    for (bullet, bullet_transform) in bullet_query.iter() {
-        for (shield_entity, shield_transform, shield) in shield_query.iter() {
-            if bullet_transform
-                .translation
-                .distance(shield_transform.translation)
-                < SHIELD_SIZE
-            {
-                commands.entity(bullet).despawn();
-                commands.entity(shield_entity).despawn();
-
-                let new_shield_level = shield.0 - 1;
-
-                if new_shield_level < 0 {
-                    continue;
-                }
+    let mut spent_bullets = Vec::new();
 
-                let shield: Shield = Shield(shield.0 - 1);
-                let shield_sprite_path = shield.get_sprite_path();
-                commands.spawn((
-                    shield,
-                    SpriteBundle {
-                        texture: asset_server.load(shield_sprite_path),
-                        transform: Transform {
-                            translation: shield_transform.translation,
-                            ..default()
-                        },
-                        ..default()
-                    },
-                ));
-            }
+    for event in collision_events.read() {
+        let CollisionEvent::BulletHitShield { bullet, shield } = *event else {
+            continue;
+        };
+        if spent_bullets.contains(&bullet) {
+            continue;
+        }
+        let Ok((bullet_transform, hitbox, velocity)) = bullet_query.get(bullet) else {
+            continue;
+        };
+        let Ok((shield_transform, mut shield)) = shield_query.get_mut(shield) else {
+            continue;
+        };
+
+        // The hitboxes touched, but the bullet may still be flying through a hole. Everything it
+        // passed over this tick is checked so a fast bullet can't skip the cells in between.
+        let end = bullet_transform.translation;
+        let start = end - velocity.0 * time.delta_seconds();
+        let (columns, rows) = covered_cells(
+            shield_transform.translation,
+            hitbox.bounds(start).union(hitbox.bounds(end)),
+        );
+        let moving_up = velocity.0.y > 0.0;
+        let Some((column, row)) = shield.mask.first_hit(columns, rows, moving_up) else {
+            continue;
+        };
+
+        // The center of the stamp is solid, so every hit removes at least the cell it struck
+        shield.mask.carve(column, row, config.shields.crater_size);
+        commands.entity(bullet).despawn();
+        spent_bullets.push(bullet);
+    }
+}
+
+/// Invaders that reach the shields destroy whatever part of them they overlap
+pub(crate) fn erode_shields_under_invaders(
+    mut collision_events: EventReader<CollisionEvent>,
+    invader_query: Query<(&Transform, &Hitbox), With<Invader>>,
+    mut shield_query: Query<(&Transform, &mut Shield)>,
+) {
+    for event in collision_events.read() {
+        let CollisionEvent::InvaderHitShield { invader, shield } = *event else {
+            continue;
+        };
+        let Ok((invader_transform, hitbox)) = invader_query.get(invader) else {
+            continue;
+        };
+        let Ok((shield_transform, mut shield)) = shield_query.get_mut(shield) else {
+            continue;
+        };
+
+        let (columns, rows) = covered_cells(
+            shield_transform.translation,
+            hitbox.bounds(invader_transform.translation),
+        );
+
+        // Only touch the mask when something is left to destroy, so the sprite isn't redrawn
+        // every tick
+        if shield.mask.first_hit(columns, rows, false).is_some() {
+            shield.mask.clear(columns, rows);
+        }
+    }
+}
+
+/// Draws the sprite of every new shield and redraws it whenever its mask changes
+pub(crate) fn update_shield_textures(
+    mut shield_query: Query<(Ref<Shield>, &mut Handle<Image>), Changed<Shield>>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    for (shield, mut texture) in shield_query.iter_mut() {
+        let image = shield.mask.to_image();
+        if shield.is_added() {
+            *texture = images.add(image);
+        } else if let Some(existing) = images.get_mut(texture.id()) {
+            *existing = image;
         }
     }
 }
@@ -0,0 +1,182 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameAssets,
+    config::GameConfig,
+    invaders::{self, InvaderConfig, InvaderShootState},
+    levels::LevelSequence,
+    mystery_ship::MysteryShipConfig,
+    player::{Dying, Lives, LivesConfig, PlayerRespawnTimer},
+    rng::GameRng,
+    shields::{self, ShieldMask},
+    CommonBullet, Invader, MysteryShip, Player, Score, Shield, TurnOverEvent, Wave,
+};
+
+pub const MAX_PLAYERS: u32 = 2;
+
+/// How many players take turns in a round
+#[derive(Resource, Debug)]
+pub struct PlayersConfig {
+    pub players: u32,
+}
+
+impl Default for PlayersConfig {
+    fn default() -> Self {
+        PlayersConfig { players: 1 }
+    }
+}
+
+/// Everything a player left behind when their turn ended, put back when it is their turn again
+struct Board {
+    score: u32,
+    lives: Lives,
+    wave: u32,
+    invader_config: InvaderConfig,
+    invader_shoot_state: InvaderShootState,
+    mystery_ship_config: MysteryShipConfig,
+    invaders: Vec<(Invader, Vec3)>,
+    shields: Vec<(ShieldMask, Vec3)>,
+}
+
+impl Board {
+    /// The first wave, as the player waiting for their first turn will find it
+    fn new(
+        levels: &LevelSequence,
+        config: &GameConfig,
+        lives_config: &LivesConfig,
+        game_rng: &mut GameRng,
+    ) -> Self {
+        Board {
+            score: 0,
+            lives: Lives::new(lives_config),
+            wave: 1,
+            invader_config: InvaderConfig::new(1, levels, &config.invaders),
+            invader_shoot_state: InvaderShootState::new(config),
+            mystery_ship_config: MysteryShipConfig::new(game_rng, config),
+            invaders: invaders::formation(levels, &config.invaders, 1),
+            shields: shields::shield_row(levels, 1)
+                .into_iter()
+                .map(|position| (ShieldMask::default(), position))
+                .collect(),
+        }
+    }
+}
+
+/// Present in two-player rounds. The resources and entities of the round always belong to the
+/// player whose turn it is, the other player's board is kept here until the turns switch.
+#[derive(Resource)]
+pub struct Turns {
+    current: usize,
+    waiting: Board,
+}
+
+impl Turns {
+    /// The player whose turn it is, starting at 0 for player one
+    pub fn current_player(&self) -> usize {
+        self.current
+    }
+
+    /// Scores of both players, the current one is read from `score`
+    pub fn scores(&self, score: &Score) -> [u32; 2] {
+        let mut scores = [self.waiting.score; 2];
+        scores[self.current] = score.0;
+        scores
+    }
+
+    pub(crate) fn waiting_player_can_play(&self) -> bool {
+        self.waiting.lives.remaining > 0
+    }
+}
+
+pub(crate) fn setup(
+    mut commands: Commands,
+    players_config: Res<PlayersConfig>,
+    levels: Res<LevelSequence>,
+    config: Res<GameConfig>,
+    lives_config: Res<LivesConfig>,
+    mut game_rng: ResMut<GameRng>,
+) {
+    if players_config.players < 2 {
+        commands.remove_resource::<Turns>();
+        return;
+    }
+
+    commands.insert_resource(Turns {
+        current: 0,
+        waiting: Board::new(&levels, &config, &lives_config, &mut game_rng),
+    });
+}
+
+/// Saves the board of the player whose turn ended and puts the other player's board in its place
+pub(crate) fn switch_turns(
+    mut commands: Commands,
+    mut turn_over_event: EventReader<TurnOverEvent>,
+    mut turns: ResMut<Turns>,
+    mut score: ResMut<Score>,
+    mut lives: ResMut<Lives>,
+    mut wave: ResMut<Wave>,
+    mut invader_config: ResMut<InvaderConfig>,
+    mut invader_shoot_state: ResMut<InvaderShootState>,
+    mut mystery_ship_config: ResMut<MysteryShipConfig>,
+    game_assets: Res<GameAssets>,
+    config: Res<GameConfig>,
+    invader_query: Query<(Entity, &Invader, &Transform)>,
+    shield_query: Query<(Entity, &Shield, &Transform)>,
+    cleared_query: Query<
+        Entity,
+        Or<(
+            (With<Player>, Without<Dying>),
+            With<CommonBullet>,
+            With<MysteryShip>,
+        )>,
+    >,
+) {
+    if turn_over_event.read().next().is_none() {
+        return;
+    }
+
+    let turns = &mut *turns;
+    let waiting = &mut turns.waiting;
+    std::mem::swap(&mut score.0, &mut waiting.score);
+    std::mem::swap(&mut *lives, &mut waiting.lives);
+    std::mem::swap(&mut wave.0, &mut waiting.wave);
+    std::mem::swap(&mut *invader_config, &mut waiting.invader_config);
+    std::mem::swap(&mut *invader_shoot_state, &mut waiting.invader_shoot_state);
+    std::mem::swap(&mut *mystery_ship_config, &mut waiting.mystery_ship_config);
+
+    let saved_invaders = invader_query
+        .iter()
+        .map(|(entity, invader, transform)| {
+            commands.entity(entity).despawn();
+            let invader = Invader {
+                difficulty: invader.difficulty.clone(),
+                column: invader.column,
+            };
+            (invader, transform.translation)
+        })
+        .collect();
+    for (invader, position) in std::mem::replace(&mut waiting.invaders, saved_invaders) {
+        invaders::spawn_invader(&mut commands, &game_assets, invader, position);
+    }
+
+    let saved_shields = shield_query
+        .iter()
+        .map(|(entity, shield, transform)| {
+            commands.entity(entity).despawn();
+            (shield.mask.clone(), transform.translation)
+        })
+        .collect();
+    for (mask, position) in std::mem::replace(&mut waiting.shields, saved_shields) {
+        shields::spawn_shield(&mut commands, mask, position);
+    }
+
+    // Bullets in flight and the mystery ship belonged to the turn that just ended. A dying player
+    // is despawned by its own animation.
+    for entity in cleared_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    turns.current = 1 - turns.current;
+    info!("Player {}'s turn", turns.current + 1);
+    commands.insert_resource(PlayerRespawnTimer::new(&config));
+}
@@ -1,6 +1,6 @@
 use bevy::prelude::*;
 
-use crate::Collider;
+use crate::{CollisionLayer, Hitbox};
 
 pub(crate) const WALL_THICKNESS: f32 = 10.0;
 pub(crate) const LEFT_WALL: f32 = -450.;
@@ -15,7 +15,7 @@ struct WallBundle {
     // You can nest bundles inside of other bundles like this
     // Allowing you to compose their functionality
     sprite_bundle: SpriteBundle,
-    collider: Collider,
+    hitbox: Hitbox,
 }
 
 /// Which side of the arena is this wall located on?
@@ -71,7 +71,7 @@ impl WallBundle {
                 },
                 ..default()
             },
-            collider: Collider,
+            hitbox: Hitbox::aabb(CollisionLayer::Wall, location.size().truncate()),
         }
     }
 }
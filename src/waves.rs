@@ -0,0 +1,55 @@
+use bevy::prelude::*;
+
+use crate::{
+    assets::GameAssets,
+    config::GameConfig,
+    invaders::{self, InvaderConfig},
+    levels::LevelSequence,
+    shields, PlayerKilledAllInvadersEvent, Shield, Wave,
+};
+
+#[derive(Resource)]
+pub(crate) struct WaveConfig {
+    /// Whether damaged shields are rebuilt when a new wave starts
+    pub(crate) restore_shields: bool,
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        WaveConfig {
+            restore_shields: true,
+        }
+    }
+}
+
+pub(crate) fn start_next_wave(
+    mut commands: Commands,
+    game_assets: Res<GameAssets>,
+    mut player_killed_all_invaders_event: EventReader<PlayerKilledAllInvadersEvent>,
+    mut wave: ResMut<Wave>,
+    wave_config: Res<WaveConfig>,
+    levels: Res<LevelSequence>,
+    config: Res<GameConfig>,
+    shield_query: Query<Entity, With<Shield>>,
+) {
+    if player_killed_all_invaders_event.read().next().is_none() {
+        return;
+    }
+
+    wave.0 += 1;
+    commands.insert_resource(InvaderConfig::new(wave.0, &levels, &config.invaders));
+    invaders::spawn_formation(
+        &mut commands,
+        &game_assets,
+        &levels,
+        &config.invaders,
+        wave.0,
+    );
+
+    if wave_config.restore_shields {
+        for shield in shield_query.iter() {
+            commands.entity(shield).despawn();
+        }
+        shields::spawn_shield_row(&mut commands, &levels, wave.0);
+    }
+}
@@ -0,0 +1,389 @@
+mod common;
+
+use bevy::{ecs::event::ManualEventReader, prelude::*};
+use space_invaders::{
+    collision::SpatialGrid, CollisionEvent, CollisionLayer, CommonBullet, Hitbox, Invader,
+    InvaderBullet, InvaderBulletHitPlayerEvent, InvaderDifficulty, InvaderKilledEvent,
+    OnGameScreen, Player, PlayerBullet, Score, Shield, Velocity, BULLET_SIZE, INVADER_SIZE,
+};
+
+fn spawn_invader(app: &mut App, position: Vec3) {
+    app.world.spawn((
+        Invader {
+            difficulty: InvaderDifficulty::Medium,
+            column: 0,
+        },
+        Hitbox::aabb(CollisionLayer::Invader, INVADER_SIZE),
+        Transform::from_translation(position),
+        OnGameScreen,
+    ));
+}
+
+fn spawn_invader_bullet(app: &mut App, position: Vec3) {
+    app.world.spawn((
+        CommonBullet,
+        InvaderBullet,
+        Hitbox::aabb(CollisionLayer::InvaderBullet, BULLET_SIZE),
+        Velocity(Vec3::new(0.0, -200.0, 0.0)),
+        Transform::from_translation(position),
+        OnGameScreen,
+    ));
+}
+
+#[test]
+fn player_bullet_kills_invader_above_player() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+    common::despawn_all::<Shield>(&mut app);
+
+    let player_position = common::player_position(&mut app);
+    // A lone invader marches fast, so it starts right above the bullet
+    spawn_invader(&mut app, player_position + Vec3::new(0.0, 40.0, 0.0));
+    spawn_invader(&mut app, Vec3::new(0.0, 250.0, 0.0));
+    app.world.spawn((
+        CommonBullet,
+        PlayerBullet,
+        Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+        Velocity(Vec3::new(0.0, 500.0, 0.0)),
+        Transform::from_translation(player_position + Vec3::new(0.0, 10.0, 0.0)),
+        OnGameScreen,
+    ));
+
+    let killed = common::step_counting::<InvaderKilledEvent>(&mut app, 10);
+
+    assert_eq!(killed, 1);
+    assert_eq!(common::count::<Invader>(&mut app), 1);
+    assert_eq!(common::count::<PlayerBullet>(&mut app), 0);
+    assert_eq!(app.world.resource::<Score>().0, 20);
+}
+
+fn shield_cells(app: &mut App, position: Vec3) -> Option<usize> {
+    app.world
+        .query::<(&Transform, &Shield)>()
+        .iter(&app.world)
+        .find(|(transform, _)| transform.translation == position)
+        .map(|(_, shield)| shield.mask.solid_cells())
+}
+
+fn first_shield_position(app: &mut App) -> Vec3 {
+    app.world
+        .query_filtered::<&Transform, With<Shield>>()
+        .iter(&app.world)
+        .next()
+        .unwrap()
+        .translation
+}
+
+#[test]
+fn invader_bullet_carves_shield() {
+    let mut app = common::test_app();
+
+    let shield_position = first_shield_position(&mut app);
+    let intact_cells = shield_cells(&mut app, shield_position).unwrap();
+    spawn_invader_bullet(&mut app, shield_position + Vec3::new(0.0, 30.0, 0.0));
+
+    common::step(&mut app, 5);
+
+    let cells = shield_cells(&mut app, shield_position).unwrap();
+    assert!(
+        cells < intact_cells,
+        "{} of {} cells left",
+        cells,
+        intact_cells
+    );
+    assert!(
+        cells > intact_cells - 100,
+        "{} of {} cells left",
+        cells,
+        intact_cells
+    );
+    assert_eq!(common::count::<InvaderBullet>(&mut app), 0);
+}
+
+#[test]
+fn bullets_pass_through_holes_in_shields() {
+    let mut app = common::test_app();
+
+    let shield_position = first_shield_position(&mut app);
+    let mut bullets_stopped = 0;
+
+    for _ in 0..10 {
+        app.world.spawn((
+            CommonBullet,
+            PlayerBullet,
+            Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+            Velocity(Vec3::new(0.0, 500.0, 0.0)),
+            Transform::from_translation(shield_position + Vec3::new(10.0, -40.0, 0.0)),
+            OnGameScreen,
+        ));
+        common::step(&mut app, 12);
+
+        if common::count::<PlayerBullet>(&mut app) == 0 {
+            bullets_stopped += 1;
+        } else {
+            break;
+        }
+    }
+
+    // The first bullets only dig a crater
+    assert!(bullets_stopped > 1);
+    assert_eq!(common::count::<PlayerBullet>(&mut app), 1);
+}
+
+#[test]
+fn invaders_destroy_the_shield_parts_they_overlap() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+
+    let shield_position = first_shield_position(&mut app);
+    // Half the invader hangs over the left edge of the shield
+    spawn_invader(&mut app, shield_position + Vec3::new(-30.0, 10.0, 0.0));
+
+    common::step(&mut app, 1);
+
+    let shield = app
+        .world
+        .query::<&Shield>()
+        .iter(&app.world)
+        .find(|shield| !shield.mask.is_solid(0, 0))
+        .expect("no shield was eroded");
+    // The invader covers the top left of the shield but not its right side or its feet
+    assert!(!shield.mask.is_solid(10, 13));
+    assert!(!shield.mask.is_solid(0, 20));
+    assert!(shield.mask.is_solid(59, 0));
+    assert!(shield.mask.is_solid(0, 29));
+    assert_eq!(common::count::<Invader>(&mut app), 1);
+}
+
+#[test]
+fn invader_bullet_hits_player() {
+    let mut app = common::test_app();
+
+    let player_position = common::player_position(&mut app);
+    spawn_invader_bullet(&mut app, player_position + Vec3::new(0.0, 20.0, 0.0));
+
+    let hits = common::step_counting::<InvaderBulletHitPlayerEvent>(&mut app, 5);
+
+    assert_eq!(hits, 1);
+    assert_eq!(common::count::<InvaderBullet>(&mut app), 0);
+}
+
+#[test]
+fn collision_events_follow_hitbox_offsets() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+
+    let position = Vec3::new(0.0, 100.0, 0.0);
+    let invader = app
+        .world
+        .spawn((
+            Invader {
+                difficulty: InvaderDifficulty::Easy,
+                column: 0,
+            },
+            Hitbox::circle(CollisionLayer::Invader, 10.0).with_offset(Vec2::new(50.0, 0.0)),
+            Transform::from_translation(position),
+            OnGameScreen,
+        ))
+        .id();
+    let spawn_still_bullet = |app: &mut App, x: f32| {
+        app.world
+            .spawn((
+                CommonBullet,
+                PlayerBullet,
+                Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+                Velocity(Vec3::ZERO),
+                Transform::from_translation(position + Vec3::new(x, 0.0, 0.0)),
+                OnGameScreen,
+            ))
+            .id()
+    };
+    let missed_bullet = spawn_still_bullet(&mut app, 0.0);
+    let bullet = spawn_still_bullet(&mut app, 55.0);
+
+    let mut reader = ManualEventReader::<CollisionEvent>::default();
+    app.update();
+    let events: Vec<CollisionEvent> = reader
+        .read(app.world.resource::<Events<CollisionEvent>>())
+        .copied()
+        .collect();
+
+    assert_eq!(
+        events,
+        vec![CollisionEvent::PlayerBulletHitInvader { bullet, invader }]
+    );
+    assert!(app.world.get_entity(missed_bullet).is_some());
+}
+
+#[test]
+fn fast_bullet_cannot_skip_over_the_player() {
+    let mut app = common::test_app();
+
+    let player_position = common::player_position(&mut app);
+    // At 60 ticks per second the bullet travels 100 units per tick, further than any hitbox is
+    // tall
+    app.world.spawn((
+        CommonBullet,
+        InvaderBullet,
+        Hitbox::aabb(CollisionLayer::InvaderBullet, BULLET_SIZE),
+        Velocity(Vec3::new(0.0, -6000.0, 0.0)),
+        Transform::from_translation(player_position + Vec3::new(0.0, 60.0, 0.0)),
+        OnGameScreen,
+    ));
+
+    // The bullet ends the tick below the player, in the floor
+    let hits = common::step_counting::<InvaderBulletHitPlayerEvent>(&mut app, 1);
+
+    assert_eq!(hits, 1);
+    assert_eq!(common::count::<InvaderBullet>(&mut app), 0);
+}
+
+#[test]
+fn fast_bullet_cannot_skip_over_a_shield() {
+    let mut app = common::test_app();
+
+    let shield_position = first_shield_position(&mut app);
+    let intact_cells = shield_cells(&mut app, shield_position).unwrap();
+    app.world.spawn((
+        CommonBullet,
+        PlayerBullet,
+        Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+        Velocity(Vec3::new(0.0, 6000.0, 0.0)),
+        Transform::from_translation(shield_position + Vec3::new(0.0, -40.0, 0.0)),
+        OnGameScreen,
+    ));
+
+    common::step(&mut app, 1);
+
+    assert!(shield_cells(&mut app, shield_position).unwrap() < intact_cells);
+    assert_eq!(common::count::<PlayerBullet>(&mut app), 0);
+}
+
+#[test]
+fn shield_stops_a_bullet_before_the_invader_behind_it() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+
+    let shield_position = first_shield_position(&mut app);
+    let intact_cells = shield_cells(&mut app, shield_position).unwrap();
+    spawn_invader(&mut app, shield_position + Vec3::new(0.0, 60.0, 0.0));
+    spawn_invader(&mut app, Vec3::new(0.0, 250.0, 0.0));
+    app.world.spawn((
+        CommonBullet,
+        PlayerBullet,
+        Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+        Velocity(Vec3::new(0.0, 6000.0, 0.0)),
+        Transform::from_translation(shield_position + Vec3::new(0.0, -40.0, 0.0)),
+        OnGameScreen,
+    ));
+
+    // The bullet ends the tick inside the invader, but the shield is in the way
+    let killed = common::step_counting::<InvaderKilledEvent>(&mut app, 1);
+
+    assert_eq!(killed, 0);
+    assert!(shield_cells(&mut app, shield_position).unwrap() < intact_cells);
+    assert_eq!(common::count::<PlayerBullet>(&mut app), 0);
+}
+
+#[test]
+fn fast_bullet_kills_the_first_invader_in_its_path() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+    common::despawn_all::<Shield>(&mut app);
+
+    let player_position = common::player_position(&mut app);
+    spawn_invader(&mut app, player_position + Vec3::new(0.0, 60.0, 0.0));
+    spawn_invader(&mut app, player_position + Vec3::new(0.0, 110.0, 0.0));
+    app.world.spawn((
+        CommonBullet,
+        PlayerBullet,
+        Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+        Velocity(Vec3::new(0.0, 6000.0, 0.0)),
+        Transform::from_translation(player_position + Vec3::new(0.0, 10.0, 0.0)),
+        OnGameScreen,
+    ));
+
+    // The bullet ends the tick inside the far invader
+    let killed = common::step_counting::<InvaderKilledEvent>(&mut app, 1);
+
+    assert_eq!(killed, 1);
+    let survivor = app
+        .world
+        .query_filtered::<&Transform, With<Invader>>()
+        .single(&app.world)
+        .translation;
+    assert!(survivor.y > player_position.y + 100.0);
+}
+
+#[test]
+fn spatial_grid_reports_each_overlap_once() {
+    let mut grid = SpatialGrid::new(10.0);
+    let wide = Entity::from_raw(0);
+    let small = Entity::from_raw(1);
+    let far = Entity::from_raw(2);
+    let touching = Entity::from_raw(3);
+    // The wide box spans many cells, and shares several of them with the small one
+    grid.insert(wide, Rect::new(-25.0, -5.0, 25.0, 5.0));
+    grid.insert(small, Rect::new(-12.0, -3.0, 12.0, 3.0));
+    grid.insert(far, Rect::new(100.0, 100.0, 110.0, 110.0));
+    grid.insert(touching, Rect::new(25.0, -5.0, 35.0, 5.0));
+
+    assert_eq!(grid.pairs(), vec![(wide, small)]);
+}
+
+#[test]
+fn fast_bullet_flies_past_an_invulnerable_player_into_the_floor() {
+    let mut app = common::test_app();
+
+    // A respawned player can't be hit for a while
+    app.world.send_event(InvaderBulletHitPlayerEvent);
+    while common::count::<Player>(&mut app) > 0 {
+        common::step(&mut app, 1);
+    }
+    while common::count::<Player>(&mut app) == 0 {
+        common::step(&mut app, 1);
+    }
+    common::despawn_all::<InvaderBullet>(&mut app);
+
+    let player_position = common::player_position(&mut app);
+    app.world.spawn((
+        CommonBullet,
+        InvaderBullet,
+        Hitbox::aabb(CollisionLayer::InvaderBullet, BULLET_SIZE),
+        Velocity(Vec3::new(0.0, -6000.0, 0.0)),
+        Transform::from_translation(player_position + Vec3::new(0.0, 60.0, 0.0)),
+        OnGameScreen,
+    ));
+
+    let hits = common::step_counting::<InvaderBulletHitPlayerEvent>(&mut app, 1);
+
+    assert_eq!(hits, 0);
+    assert_eq!(common::count::<InvaderBullet>(&mut app), 0);
+}
+
+#[test]
+fn fast_bullet_flies_past_an_invader_another_bullet_killed() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+    common::despawn_all::<Shield>(&mut app);
+
+    let player_position = common::player_position(&mut app);
+    spawn_invader(&mut app, player_position + Vec3::new(0.0, 60.0, 0.0));
+    spawn_invader(&mut app, player_position + Vec3::new(0.0, 110.0, 0.0));
+    for x in [-10.0, 10.0] {
+        app.world.spawn((
+            CommonBullet,
+            PlayerBullet,
+            Hitbox::aabb(CollisionLayer::PlayerBullet, BULLET_SIZE),
+            Velocity(Vec3::new(0.0, 6000.0, 0.0)),
+            Transform::from_translation(player_position + Vec3::new(x, 10.0, 0.0)),
+            OnGameScreen,
+        ));
+    }
+
+    // Both bullets reach the near invader together, only one of them is spent on it
+    let killed = common::step_counting::<InvaderKilledEvent>(&mut app, 1);
+
+    assert_eq!(killed, 2);
+    assert_eq!(common::count::<PlayerBullet>(&mut app), 0);
+}
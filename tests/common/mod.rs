@@ -0,0 +1,157 @@
+// Each test binary only uses some of the helpers
+#![allow(dead_code)]
+
+use std::path::{Path, PathBuf};
+
+use bevy::{
+    ecs::event::ManualEventReader,
+    input::{keyboard::KeyboardInput, ButtonState},
+    prelude::*,
+};
+use space_invaders::{
+    cli::CliArgs,
+    game::GamePlugin,
+    headless,
+    levels::LevelSequence,
+    replay::{ReplayPlayback, ReplayRecording},
+    turns::PlayersConfig,
+};
+
+/// A headless game that has already entered its first round, stepping one simulation tick per
+/// update
+pub fn test_app() -> App {
+    test_app_with_levels(LevelSequence::default())
+}
+
+pub fn test_app_with_levels(levels: LevelSequence) -> App {
+    build_test_app(levels, PlayersConfig::default())
+}
+
+/// A headless two-player round, player one takes the first turn
+pub fn two_player_app() -> App {
+    build_test_app(LevelSequence::default(), PlayersConfig { players: 2 })
+}
+
+fn build_test_app(levels: LevelSequence, players_config: PlayersConfig) -> App {
+    build_app(|app| {
+        app.insert_resource(levels).insert_resource(players_config);
+    })
+}
+
+/// A headless round whose inputs are recorded to `path` once it ends
+pub fn recording_app(path: PathBuf) -> App {
+    build_app(|app| {
+        app.insert_resource(ReplayRecording::new(path));
+    })
+}
+
+/// A headless round played back from the replay at `path`, with the seed, tuning and levels it
+/// was recorded with
+pub fn playback_app(path: &Path) -> App {
+    let playback = ReplayPlayback::load(path).unwrap();
+    build_app(|app| {
+        app.insert_resource(CliArgs {
+            seed: Some(playback.replay().seed),
+            tick_rate: Some(playback.replay().tick_rate),
+            ..default()
+        })
+        .insert_resource(playback.replay().config.clone())
+        .insert_resource(playback.replay().levels.clone())
+        .insert_resource(playback);
+    })
+}
+
+fn build_app(configure: impl FnOnce(&mut App)) -> App {
+    let mut app = App::new();
+    app.add_plugins(headless::HeadlessPlugins)
+        .insert_resource(CliArgs {
+            seed: Some(1),
+            ..default()
+        })
+        .insert_resource(headless::HeadlessRun::new(None));
+    configure(&mut app);
+    app.add_plugins(GamePlugin);
+
+    // The first update only enters the round
+    app.update();
+    app
+}
+
+pub fn step(app: &mut App, frames: usize) {
+    for _ in 0..frames {
+        app.update();
+    }
+}
+
+/// Steps the app and counts the events of type `E` sent in the meantime
+pub fn step_counting<E: Event>(app: &mut App, frames: usize) -> usize {
+    let mut reader = ManualEventReader::<E>::default();
+    let mut count = 0;
+
+    for _ in 0..frames {
+        app.update();
+        count += reader.read(app.world.resource::<Events<E>>()).count();
+    }
+
+    count
+}
+
+pub fn despawn_all<T: Component>(app: &mut App) {
+    let entities: Vec<Entity> = app
+        .world
+        .query_filtered::<Entity, With<T>>()
+        .iter(&app.world)
+        .collect();
+
+    for entity in entities {
+        app.world.entity_mut(entity).despawn_recursive();
+    }
+}
+
+pub fn count<T: Component>(app: &mut App) -> usize {
+    app.world
+        .query_filtered::<(), With<T>>()
+        .iter(&app.world)
+        .count()
+}
+
+pub fn player_position(app: &mut App) -> Vec3 {
+    app.world
+        .query_filtered::<&Transform, With<space_invaders::Player>>()
+        .single(&app.world)
+        .translation
+}
+
+/// Presses and releases a key, one update each. Pressing `Input<KeyCode>` directly would only
+/// hold the key down, the press itself is cleared before any system sees it.
+pub fn tap(app: &mut App, key_code: KeyCode) {
+    for state in [ButtonState::Pressed, ButtonState::Released] {
+        app.world.send_event(KeyboardInput {
+            scan_code: 0,
+            key_code: Some(key_code),
+            state,
+            window: Entity::PLACEHOLDER,
+        });
+        app.update();
+    }
+}
+
+/// Pauses the round and waits for the pause menu to show up
+pub fn open_pause_menu(app: &mut App) {
+    tap(app, KeyCode::Escape);
+    step(app, 2);
+}
+
+/// Presses the button of the menu on screen with the given label, then waits for the screen it
+/// leads to
+pub fn press_button(app: &mut App, label: &str) {
+    let button = app
+        .world
+        .query::<(&Parent, &Text)>()
+        .iter(&app.world)
+        .find(|(_, text)| text.sections[0].value == label)
+        .map(|(parent, _)| parent.get())
+        .unwrap_or_else(|| panic!("no {} button", label));
+    *app.world.get_mut::<Interaction>(button).unwrap() = Interaction::Pressed;
+    step(app, 2);
+}
@@ -0,0 +1,63 @@
+mod common;
+
+use bevy::prelude::*;
+use space_invaders::{config::GameConfig, Player};
+
+#[test]
+fn shipped_config_matches_defaults() {
+    let path = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join("config.ron");
+
+    assert_eq!(GameConfig::load(&path), Ok(Some(GameConfig::default())));
+}
+
+#[test]
+fn missing_fields_keep_their_defaults() {
+    let config = GameConfig::from_ron("(player: (speed: 250.0))").unwrap();
+
+    assert_eq!(config.player.speed, 250.0);
+    assert_eq!(
+        config.player.fire_rate,
+        GameConfig::default().player.fire_rate
+    );
+    assert_eq!(config.shields, GameConfig::default().shields);
+}
+
+#[test]
+fn invalid_values_are_all_reported() {
+    let error = GameConfig::from_ron(
+        "(player: (speed: -1.0), invaders: (wave_speedup: 1.5), invader_fire: (patterns: []))",
+    )
+    .unwrap_err();
+
+    assert!(error.contains("player.speed must be greater than 0"));
+    assert!(error.contains("invaders.wave_speedup must be in (0, 1]"));
+    assert!(error.contains("invader_fire.patterns"));
+}
+
+#[test]
+fn unknown_fields_are_rejected() {
+    assert!(GameConfig::from_ron("(player: (sped: 250.0))").is_err());
+}
+
+#[test]
+fn config_changes_apply_while_running() {
+    let mut app = common::test_app();
+    app.world.resource_mut::<GameConfig>().player.speed = 60.0;
+
+    let start = common::player_position(&mut app);
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Right);
+    // Drawn positions trail the simulation by one tick
+    common::step(&mut app, 61);
+    let moved = common::player_position(&mut app).x - start.x;
+
+    assert!((moved - 60.0).abs() < 0.01, "moved {}", moved);
+    assert_eq!(
+        common::count::<Player>(&mut app),
+        1,
+        "the player should still be alive"
+    );
+}
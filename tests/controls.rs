@@ -0,0 +1,79 @@
+mod common;
+
+use bevy::prelude::*;
+use space_invaders::{
+    controls::{Action, InputMap},
+    PlayerBulletFiredEvent,
+};
+
+#[test]
+fn keys_bound_to_another_action_are_refused() {
+    let mut input_map = InputMap::default();
+
+    assert_eq!(
+        input_map.bind(Action::Fire, KeyCode::A),
+        Err(Action::MoveLeft)
+    );
+    assert_eq!(input_map.keys(Action::Fire), &[KeyCode::Space]);
+
+    assert_eq!(input_map.bind(Action::Fire, KeyCode::F), Ok(()));
+    assert_eq!(input_map.keys(Action::Fire), &[KeyCode::F]);
+    assert_eq!(input_map.action(KeyCode::Space), None);
+}
+
+#[test]
+fn controls_file_round_trips_and_rejects_conflicts() {
+    let mut input_map = InputMap::default();
+    input_map.bind(Action::MoveLeft, KeyCode::J).unwrap();
+
+    let contents = input_map.to_ron().unwrap();
+    assert_eq!(InputMap::from_ron(&contents), Ok(input_map));
+
+    let conflicting = contents.replace("J", "Space");
+    let error = InputMap::from_ron(&conflicting).unwrap_err();
+    assert!(error.contains("Space is bound to both"), "{}", error);
+}
+
+#[test]
+fn the_player_fires_with_the_bound_key() {
+    let mut app = common::test_app();
+    let mut input_map = InputMap::default();
+    input_map.bind(Action::Fire, KeyCode::F).unwrap();
+    app.world.insert_resource(input_map);
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Space);
+    let shots = common::step_counting::<PlayerBulletFiredEvent>(&mut app, 15);
+    assert_eq!(shots, 0);
+
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::F);
+    let shots = common::step_counting::<PlayerBulletFiredEvent>(&mut app, 15);
+    assert!(shots > 0);
+}
+
+#[test]
+fn actions_are_rebound_from_the_controls_screen() {
+    let mut app = common::test_app();
+
+    common::open_pause_menu(&mut app);
+    common::press_button(&mut app, "Options");
+    common::press_button(&mut app, "Controls");
+
+    common::press_button(&mut app, "Fire: Space");
+    common::tap(&mut app, KeyCode::Left);
+    assert_eq!(
+        app.world.resource::<InputMap>().keys(Action::Fire),
+        &[KeyCode::Space]
+    );
+
+    common::press_button(&mut app, "Fire: Space");
+    common::tap(&mut app, KeyCode::F);
+    assert_eq!(
+        app.world.resource::<InputMap>().keys(Action::Fire),
+        &[KeyCode::F]
+    );
+
+    common::press_button(&mut app, "Reset");
+    assert_eq!(*app.world.resource::<InputMap>(), InputMap::default());
+}
@@ -0,0 +1,62 @@
+mod common;
+
+use bevy::prelude::*;
+use space_invaders::{turns::Turns, Invader, Player, Score};
+
+#[derive(Debug, PartialEq)]
+struct Outcome {
+    scores: Vec<u32>,
+    current_player: usize,
+    invaders: usize,
+    player_positions: Vec<Vec3>,
+}
+
+fn play(mut app: App, frames: usize) -> Outcome {
+    for frame in 0..frames {
+        let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+        keyboard_input.press(KeyCode::Space);
+        if frame % 120 < 60 {
+            keyboard_input.release(KeyCode::Right);
+            keyboard_input.press(KeyCode::Left);
+        } else {
+            keyboard_input.release(KeyCode::Left);
+            keyboard_input.press(KeyCode::Right);
+        }
+        app.update();
+    }
+
+    let score = app.world.resource::<Score>();
+    let (scores, current_player) = match app.world.get_resource::<Turns>() {
+        Some(turns) => (turns.scores(score).to_vec(), turns.current_player()),
+        None => (vec![score.0], 0),
+    };
+    let player_positions = app
+        .world
+        .query_filtered::<&Transform, With<Player>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation)
+        .collect();
+    Outcome {
+        scores,
+        current_player,
+        invaders: common::count::<Invader>(&mut app),
+        player_positions,
+    }
+}
+
+#[test]
+fn same_seed_and_inputs_play_out_the_same() {
+    let first = play(common::test_app(), 600);
+
+    assert!(first.scores[0] > 0);
+    assert_eq!(first, play(common::test_app(), 600));
+}
+
+#[test]
+fn two_player_rounds_play_out_the_same() {
+    let first = play(common::two_player_app(), 3000);
+
+    // Both players had a turn
+    assert!(first.scores.iter().all(|&score| score > 0));
+    assert_eq!(first, play(common::two_player_app(), 3000));
+}
@@ -0,0 +1,128 @@
+mod common;
+
+use bevy::{
+    input::gamepad::{
+        GamepadAxisChangedEvent, GamepadButtonChangedEvent, GamepadConnection,
+        GamepadConnectionEvent, GamepadEvent, GamepadInfo,
+    },
+    prelude::*,
+};
+use space_invaders::{input::GamepadSlots, pause::PauseState, PlayerBulletFiredEvent};
+
+fn connect(app: &mut App, id: usize) -> Gamepad {
+    let gamepad = Gamepad::new(id);
+    app.world
+        .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Connected(GamepadInfo {
+                name: format!("Test pad {}", id),
+            }),
+        )));
+    app.update();
+    gamepad
+}
+
+fn disconnect(app: &mut App, gamepad: Gamepad) {
+    app.world
+        .send_event(GamepadEvent::Connection(GamepadConnectionEvent::new(
+            gamepad,
+            GamepadConnection::Disconnected,
+        )));
+    app.update();
+}
+
+fn set_button(app: &mut App, gamepad: Gamepad, button_type: GamepadButtonType, value: f32) {
+    app.world
+        .send_event(GamepadEvent::Button(GamepadButtonChangedEvent::new(
+            gamepad,
+            button_type,
+            value,
+        )));
+}
+
+fn set_stick(app: &mut App, gamepad: Gamepad, x: f32) {
+    app.world
+        .send_event(GamepadEvent::Axis(GamepadAxisChangedEvent::new(
+            gamepad,
+            GamepadAxisType::LeftStickX,
+            x,
+        )));
+}
+
+fn player_moved_by(app: &mut App, frames: usize) -> f32 {
+    // Drawn positions trail the simulation by a tick, let the previous move settle first
+    common::step(app, 2);
+    let start = common::player_position(app).x;
+    common::step(app, frames);
+    common::player_position(app).x - start
+}
+
+#[test]
+fn stick_and_dpad_move_the_player() {
+    let mut app = common::test_app();
+    let gamepad = connect(&mut app, 0);
+
+    set_stick(&mut app, gamepad, 0.9);
+    assert!(player_moved_by(&mut app, 10) > 0.0);
+
+    // A stick resting slightly off center doesn't count
+    set_stick(&mut app, gamepad, -0.2);
+    assert_eq!(player_moved_by(&mut app, 10), 0.0);
+
+    set_stick(&mut app, gamepad, 0.0);
+    set_button(&mut app, gamepad, GamepadButtonType::DPadLeft, 1.0);
+    assert!(player_moved_by(&mut app, 10) < 0.0);
+}
+
+#[test]
+fn face_buttons_fire() {
+    let mut app = common::test_app();
+    let gamepad = connect(&mut app, 0);
+
+    set_button(&mut app, gamepad, GamepadButtonType::South, 1.0);
+    let shots = common::step_counting::<PlayerBulletFiredEvent>(&mut app, 15);
+
+    assert!(shots > 0);
+}
+
+#[test]
+fn start_pauses_the_round() {
+    let mut app = common::test_app();
+    let gamepad = connect(&mut app, 0);
+
+    set_button(&mut app, gamepad, GamepadButtonType::Start, 1.0);
+    common::step(&mut app, 2);
+
+    assert_eq!(
+        *app.world.resource::<State<PauseState>>().get(),
+        PauseState::Paused
+    );
+}
+
+#[test]
+fn gamepads_keep_their_player_across_hot_plugging() {
+    let mut app = common::test_app();
+    let first = connect(&mut app, 0);
+    let second = connect(&mut app, 1);
+    let third = connect(&mut app, 2);
+
+    let slots = app.world.resource::<GamepadSlots>();
+    assert_eq!(slots.gamepad(0), Some(first));
+    assert_eq!(slots.gamepad(1), Some(second));
+
+    // The pad left waiting takes the free slot
+    disconnect(&mut app, first);
+    let slots = app.world.resource::<GamepadSlots>();
+    assert_eq!(slots.gamepad(0), Some(third));
+    assert_eq!(slots.gamepad(1), Some(second));
+
+    // With no pad waiting, the slot stays free for the next one plugged in
+    disconnect(&mut app, second);
+    let slots = app.world.resource::<GamepadSlots>();
+    assert_eq!(slots.gamepad(1), None);
+
+    let replacement = connect(&mut app, 3);
+    let slots = app.world.resource::<GamepadSlots>();
+    assert_eq!(slots.gamepad(0), Some(third));
+    assert_eq!(slots.gamepad(1), Some(replacement));
+}
@@ -0,0 +1,97 @@
+mod common;
+
+use space_invaders::{
+    config::GameConfig,
+    invaders,
+    levels::{Level, LevelSequence},
+    Invader, Shield, Wave,
+};
+
+fn level(formation: &str) -> Level {
+    Level::from_ron(formation).unwrap()
+}
+
+#[test]
+fn shipped_sequence_starts_with_the_classic_formation() {
+    let directory = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join("levels");
+    let levels = LevelSequence::load(&directory).unwrap().unwrap();
+
+    assert_eq!(levels.levels[0], Level::default());
+    assert!(levels.levels.len() > 1);
+}
+
+#[test]
+fn every_wave_marches_faster_than_the_last() {
+    let directory = std::path::Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("assets")
+        .join("levels");
+    let levels = LevelSequence::load(&directory).unwrap().unwrap();
+    let tuning = GameConfig::default().invaders;
+
+    // Two passes, the slower first level comes back after the faster ones
+    let rates: Vec<f32> = (1..=2 * levels.levels.len() as u32)
+        .map(|wave| invaders::wave_movement_rate(wave, &levels, &tuning))
+        .collect();
+
+    for pair in rates.windows(2) {
+        assert!(pair[1] < pair[0], "rates should decrease: {:?}", rates);
+    }
+}
+
+#[test]
+fn overrides_can_be_written_without_some() {
+    let level = level(r#"(name: "Test", formation: ["E.E"], movement_rate: 0.05, shields: [0.0])"#);
+
+    assert_eq!(level.movement_rate, Some(0.05));
+    assert_eq!(level.move_down_amount, None);
+    assert_eq!(level.shields, Some(vec![0.0]));
+}
+
+#[test]
+fn invalid_levels_are_rejected() {
+    let error = Level::from_ron(
+        r#"(name: "Broken", formation: ["EEXEEEEEEEEEEEEE"], shot_interval: 0.0, shields: [900.0])"#,
+    )
+    .unwrap_err();
+
+    assert!(error.contains("'X' which is not in the legend"));
+    assert!(error.contains("at most 13 fit"));
+    assert!(error.contains("shot_interval must be greater than 0"));
+    assert!(error.contains("shield at 900 is outside the arena"));
+
+    assert!(
+        Level::from_ron(r#"(name: "Empty", formation: ["...", "   "])"#)
+            .unwrap_err()
+            .contains("no invaders")
+    );
+}
+
+#[test]
+fn waves_follow_the_level_sequence() {
+    let levels = LevelSequence {
+        levels: vec![
+            level(r#"(name: "One", formation: ["HMEMH", ".E.E."], shields: [-100.0, 100.0])"#),
+            level(r#"(name: "Two", formation: ["x"], legend: {'x': Hard}, shields: [])"#),
+        ],
+    };
+    let mut app = common::test_app_with_levels(levels);
+
+    assert_eq!(common::count::<Invader>(&mut app), 7);
+    assert_eq!(common::count::<Shield>(&mut app), 2);
+
+    common::despawn_all::<Invader>(&mut app);
+    common::step(&mut app, 1);
+
+    assert_eq!(app.world.resource::<Wave>().0, 2);
+    assert_eq!(common::count::<Invader>(&mut app), 1);
+    assert_eq!(common::count::<Shield>(&mut app), 0);
+
+    // The sequence starts over after the last level
+    common::despawn_all::<Invader>(&mut app);
+    common::step(&mut app, 1);
+
+    assert_eq!(app.world.resource::<Wave>().0, 3);
+    assert_eq!(common::count::<Invader>(&mut app), 7);
+}
@@ -0,0 +1,85 @@
+mod common;
+
+use bevy::{app::AppExit, prelude::*};
+use space_invaders::{game::GameState, pause::PauseState, Invader, OnGameScreen, Player};
+
+fn invader_positions(app: &mut App) -> Vec<Vec3> {
+    app.world
+        .query_filtered::<&Transform, With<Invader>>()
+        .iter(&app.world)
+        .map(|transform| transform.translation)
+        .collect()
+}
+
+#[test]
+fn pausing_freezes_the_round_until_resumed() {
+    let mut app = common::test_app();
+
+    common::tap(&mut app, KeyCode::P);
+    assert_eq!(
+        *app.world.resource::<State<PauseState>>().get(),
+        PauseState::Paused
+    );
+
+    let frozen = invader_positions(&mut app);
+    common::step(&mut app, 60);
+    assert_eq!(invader_positions(&mut app), frozen);
+
+    common::tap(&mut app, KeyCode::P);
+    common::step(&mut app, 60);
+    assert_eq!(
+        *app.world.resource::<State<PauseState>>().get(),
+        PauseState::Running
+    );
+    assert_ne!(invader_positions(&mut app), frozen);
+}
+
+#[test]
+fn escape_pauses_instead_of_quitting() {
+    let mut app = common::test_app();
+
+    common::tap(&mut app, KeyCode::Escape);
+
+    assert!(app.world.resource::<Events<AppExit>>().is_empty());
+    assert_eq!(
+        *app.world.resource::<State<PauseState>>().get(),
+        PauseState::Paused
+    );
+}
+
+#[test]
+fn restarting_from_the_pause_menu_starts_a_new_round() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+    let player = app
+        .world
+        .query_filtered::<Entity, With<Player>>()
+        .single(&app.world);
+
+    common::open_pause_menu(&mut app);
+    common::press_button(&mut app, "Restart Round");
+    common::step(&mut app, 1);
+
+    assert_eq!(
+        *app.world.resource::<State<PauseState>>().get(),
+        PauseState::Running
+    );
+    assert_eq!(common::count::<Invader>(&mut app), 55);
+    assert_eq!(common::count::<Player>(&mut app), 1);
+    assert!(app.world.get_entity(player).is_none());
+}
+
+#[test]
+fn quitting_from_the_pause_menu_clears_the_round() {
+    let mut app = common::test_app();
+
+    common::open_pause_menu(&mut app);
+    common::press_button(&mut app, "Main Menu");
+    common::step(&mut app, 1);
+
+    assert_eq!(
+        *app.world.resource::<State<GameState>>().get(),
+        GameState::MainMenu
+    );
+    assert_eq!(common::count::<OnGameScreen>(&mut app), 0);
+}
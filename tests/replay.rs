@@ -0,0 +1,51 @@
+mod common;
+
+use bevy::prelude::*;
+use space_invaders::{game::GameState, gamestate::GameResult, Score};
+
+/// Steps until the round ends, calling `before_tick` ahead of every tick. Returns the number of
+/// ticks the round took.
+fn play_until_game_over(app: &mut App, mut before_tick: impl FnMut(&mut App, usize)) -> usize {
+    for tick in 1..=20_000 {
+        before_tick(app, tick);
+        app.update();
+        if *app.world.resource::<State<GameState>>() == GameState::PostGame {
+            return tick;
+        }
+    }
+    panic!("the round never ended");
+}
+
+/// Keeps firing while sweeping left and right
+fn sweep_and_fire(app: &mut App, tick: usize) {
+    let mut keyboard_input = app.world.resource_mut::<Input<KeyCode>>();
+    keyboard_input.press(KeyCode::Space);
+    if tick % 120 < 60 {
+        keyboard_input.release(KeyCode::Right);
+        keyboard_input.press(KeyCode::Left);
+    } else {
+        keyboard_input.release(KeyCode::Left);
+        keyboard_input.press(KeyCode::Right);
+    }
+}
+
+#[test]
+fn recorded_round_plays_back_the_same() {
+    let path = std::env::temp_dir().join(format!("space-invaders-{}.ron", std::process::id()));
+
+    let mut recording = common::recording_app(path.clone());
+    let recorded_ticks = play_until_game_over(&mut recording, sweep_and_fire);
+    let recorded_score = recording.world.resource::<Score>().0;
+    assert!(recorded_score > 0);
+
+    let mut playback = common::playback_app(&path);
+    let played_ticks = play_until_game_over(&mut playback, |_, _| {});
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(played_ticks, recorded_ticks);
+    assert_eq!(playback.world.resource::<Score>().0, recorded_score);
+    assert_eq!(
+        playback.world.resource::<GameResult>(),
+        recording.world.resource::<GameResult>()
+    );
+}
@@ -0,0 +1,169 @@
+mod common;
+
+use bevy::prelude::*;
+use space_invaders::{
+    config::GameConfig, game::GameState, invaders::ShotPattern, Invader, InvaderBulletFiredEvent,
+    InvaderDifficulty, InvadersReachedBottomEvent, OnGameScreen, PlayerBullet, Score, Wave,
+    PLAYER_Y,
+};
+
+fn type_character(app: &mut App, character: char) {
+    app.world.send_event(ReceivedCharacter {
+        window: Entity::PLACEHOLDER,
+        char: character,
+    });
+    app.update();
+}
+
+fn name_entry_prompt(app: &mut App) -> String {
+    app.world
+        .query::<&Text>()
+        .iter(&app.world)
+        .map(|text| text.sections[0].value.clone())
+        .find(|value| value.starts_with("NEW HIGH SCORE!"))
+        .expect("no name entry prompt")
+}
+
+#[test]
+fn round_starts_with_full_formation() {
+    let mut app = common::test_app();
+
+    assert_eq!(
+        *app.world.resource::<State<GameState>>().get(),
+        GameState::Game
+    );
+    assert_eq!(common::count::<Invader>(&mut app), 55);
+    assert_eq!(app.world.resource::<Wave>().0, 1);
+}
+
+#[test]
+fn holding_fire_shoots_from_the_player() {
+    let mut app = common::test_app();
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .press(KeyCode::Space);
+    // The fire rate holds back the first shot for a moment
+    common::step(&mut app, 15);
+
+    assert_eq!(common::count::<PlayerBullet>(&mut app), 1);
+}
+
+#[test]
+fn invaders_reaching_the_player_end_the_round() {
+    let mut app = common::test_app();
+    app.world.spawn((
+        Invader {
+            difficulty: InvaderDifficulty::Easy,
+            column: 0,
+        },
+        Transform::from_xyz(0.0, PLAYER_Y, 0.0),
+        OnGameScreen,
+    ));
+
+    let reached_bottom = common::step_counting::<InvadersReachedBottomEvent>(&mut app, 2);
+    common::step(&mut app, 1);
+
+    assert_eq!(reached_bottom, 1);
+    assert_eq!(
+        *app.world.resource::<State<GameState>>().get(),
+        GameState::PostGame
+    );
+}
+
+#[test]
+fn clearing_the_formation_starts_the_next_wave() {
+    let mut app = common::test_app();
+    common::despawn_all::<Invader>(&mut app);
+
+    common::step(&mut app, 2);
+
+    assert_eq!(app.world.resource::<Wave>().0, 2);
+    assert_eq!(common::count::<Invader>(&mut app), 55);
+}
+
+#[test]
+fn keys_held_when_the_round_ends_type_nothing() {
+    let mut app = common::test_app();
+    // High enough for any table
+    app.world.resource_mut::<Score>().0 = 999_999;
+    app.world.resource_mut::<Input<KeyCode>>().press(KeyCode::A);
+    app.world.spawn((
+        Invader {
+            difficulty: InvaderDifficulty::Easy,
+            column: 0,
+        },
+        Transform::from_xyz(0.0, PLAYER_Y, 0.0),
+        OnGameScreen,
+    ));
+    common::step(&mut app, 4);
+
+    // Repeats of the movement key still held
+    type_character(&mut app, 'a');
+    assert!(name_entry_prompt(&mut app).ends_with("name: _"));
+
+    app.world
+        .resource_mut::<Input<KeyCode>>()
+        .release(KeyCode::A);
+    app.update();
+    type_character(&mut app, 'b');
+
+    assert!(name_entry_prompt(&mut app).ends_with("name: B_"));
+}
+
+#[test]
+fn last_invader_never_steps_faster_than_the_minimum_rate() {
+    let mut app = common::test_app();
+    app.world
+        .resource_mut::<GameConfig>()
+        .invaders
+        .min_movement_rate = 0.05;
+    let last = app
+        .world
+        .query_filtered::<Entity, With<Invader>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    let others: Vec<Entity> = app
+        .world
+        .query_filtered::<Entity, With<Invader>>()
+        .iter(&app.world)
+        .filter(|&entity| entity != last)
+        .collect();
+    for entity in others {
+        app.world.despawn(entity);
+    }
+
+    let mut steps = 0;
+    let mut position = app.world.get::<Transform>(last).unwrap().translation;
+    for _ in 0..60 {
+        app.update();
+        let moved_to = app.world.get::<Transform>(last).unwrap().translation;
+        if moved_to != position {
+            steps += 1;
+            position = moved_to;
+        }
+    }
+
+    // One step every third tick at most
+    assert!(steps <= 21, "{} steps", steps);
+}
+
+#[test]
+fn rolling_shot_fires_from_columns_missing_from_its_table() {
+    let mut app = common::test_app();
+    app.world.resource_mut::<GameConfig>().invader_fire.patterns =
+        vec![ShotPattern::RollingSequence];
+    let others: Vec<Entity> = app
+        .world
+        .query::<(Entity, &Invader)>()
+        .iter(&app.world)
+        .filter(|(_, invader)| invader.column != 4)
+        .map(|(entity, _)| entity)
+        .collect();
+    for entity in others {
+        app.world.despawn(entity);
+    }
+
+    assert!(common::step_counting::<InvaderBulletFiredEvent>(&mut app, 300) > 0);
+}
@@ -0,0 +1,163 @@
+mod common;
+
+use bevy::prelude::*;
+use space_invaders::{
+    config::GameConfig, game::GameState, turns::Turns, Invader, InvaderBulletHitPlayerEvent,
+    InvaderDifficulty, OnGameScreen, Player, Score, Shield, Wave, PLAYER_Y,
+};
+
+/// Ticks from the player being hit until the end of the death animation
+fn death_ticks(app: &App) -> usize {
+    let config = app.world.resource::<GameConfig>();
+    (config.player.death_animation_time * 60.0).ceil() as usize + 2
+}
+
+fn respawn_ticks(app: &App) -> usize {
+    let config = app.world.resource::<GameConfig>();
+    (config.player.respawn_delay * 60.0).ceil() as usize + 2
+}
+
+/// Shoots the player in play down and waits for the next turn to hand them a new ship
+fn lose_life(app: &mut App) {
+    app.world.send_event(InvaderBulletHitPlayerEvent);
+    let ticks = death_ticks(app) + respawn_ticks(app);
+    common::step(app, ticks);
+}
+
+fn current_player(app: &App) -> usize {
+    app.world.resource::<Turns>().current_player()
+}
+
+fn despawn_invaders(app: &mut App, count: usize) {
+    let invaders: Vec<Entity> = app
+        .world
+        .query_filtered::<Entity, With<Invader>>()
+        .iter(&app.world)
+        .take(count)
+        .collect();
+    for invader in invaders {
+        app.world.despawn(invader);
+    }
+}
+
+#[test]
+fn one_player_rounds_take_no_turns() {
+    let app = common::test_app();
+
+    assert!(!app.world.contains_resource::<Turns>());
+}
+
+#[test]
+fn each_death_hands_the_board_to_the_other_player() {
+    let mut app = common::two_player_app();
+    assert_eq!(current_player(&app), 0);
+    despawn_invaders(&mut app, 5);
+    common::step(&mut app, 1);
+
+    app.world.send_event(InvaderBulletHitPlayerEvent);
+    let ticks = death_ticks(&app);
+    common::step(&mut app, ticks);
+
+    // Player two starts on a full formation while the ship of player one is gone
+    assert_eq!(current_player(&app), 1);
+    assert_eq!(common::count::<Invader>(&mut app), 55);
+    assert_eq!(common::count::<Player>(&mut app), 0);
+
+    let ticks = respawn_ticks(&app);
+    common::step(&mut app, ticks);
+    assert_eq!(common::count::<Player>(&mut app), 1);
+
+    lose_life(&mut app);
+
+    assert_eq!(current_player(&app), 0);
+    assert_eq!(common::count::<Invader>(&mut app), 50);
+}
+
+#[test]
+fn scores_waves_and_shields_stay_with_their_player() {
+    let mut app = common::two_player_app();
+    app.world.resource_mut::<Score>().0 = 120;
+    let shield = app
+        .world
+        .query_filtered::<Entity, With<Shield>>()
+        .iter(&app.world)
+        .next()
+        .unwrap();
+    app.world.despawn(shield);
+    common::despawn_all::<Invader>(&mut app);
+    common::step(&mut app, 2);
+    assert_eq!(app.world.resource::<Wave>().0, 2);
+
+    lose_life(&mut app);
+
+    assert_eq!(current_player(&app), 1);
+    assert_eq!(app.world.resource::<Score>().0, 0);
+    assert_eq!(app.world.resource::<Wave>().0, 1);
+    assert_eq!(common::count::<Shield>(&mut app), 4);
+    assert_eq!(
+        app.world
+            .resource::<Turns>()
+            .scores(app.world.resource::<Score>()),
+        [120, 0]
+    );
+
+    app.world.resource_mut::<Score>().0 = 40;
+    lose_life(&mut app);
+
+    assert_eq!(current_player(&app), 0);
+    assert_eq!(app.world.resource::<Score>().0, 120);
+    assert_eq!(app.world.resource::<Wave>().0, 2);
+    assert_eq!(
+        app.world
+            .resource::<Turns>()
+            .scores(app.world.resource::<Score>()),
+        [120, 40]
+    );
+}
+
+#[test]
+fn invaders_landing_only_end_the_game_of_the_player_in_play() {
+    let mut app = common::two_player_app();
+    app.world.spawn((
+        Invader {
+            difficulty: InvaderDifficulty::Easy,
+            column: 0,
+        },
+        Transform::from_xyz(0.0, PLAYER_Y, 0.0),
+        OnGameScreen,
+    ));
+
+    common::step(&mut app, 3);
+
+    assert_eq!(
+        *app.world.resource::<State<GameState>>().get(),
+        GameState::Game
+    );
+    assert_eq!(current_player(&app), 1);
+    assert_eq!(common::count::<Invader>(&mut app), 55);
+
+    // Player one has no lives left, player two keeps the board from now on
+    lose_life(&mut app);
+    assert_eq!(current_player(&app), 1);
+}
+
+#[test]
+fn the_game_ends_when_both_players_are_out_of_lives() {
+    let mut app = common::two_player_app();
+
+    // Three lives each, the turns alternate until both are spent
+    for death in 0..5 {
+        lose_life(&mut app);
+        assert_eq!(current_player(&app), (death + 1) % 2);
+        assert_eq!(
+            *app.world.resource::<State<GameState>>().get(),
+            GameState::Game
+        );
+    }
+    lose_life(&mut app);
+
+    assert_eq!(
+        *app.world.resource::<State<GameState>>().get(),
+        GameState::PostGame
+    );
+}